@@ -5,9 +5,15 @@ use bevy_input::{
     touch::{ForceTouch, TouchInput, TouchPhase},
     ButtonState,
 };
-use bevy_math::Vec2;
-use bevy_window::{CursorIcon, EnabledButtons, WindowLevel, WindowTheme};
-use winit::keyboard::{Key, NamedKey, NativeKey};
+use bevy_math::{IVec2, UVec2, Vec2};
+use bevy_window::{CursorGrabMode, CursorIcon, EnabledButtons, WindowLevel, WindowTheme};
+use winit::{
+    dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
+    keyboard::{Key, NamedKey, NativeKey},
+    window::BadIcon,
+};
+
+use crate::WindowIcon;
 
 pub fn convert_keyboard_input(
     keyboard_input: &winit::event::KeyEvent,
@@ -628,7 +634,9 @@ pub fn convert_native_key(native_key: &NativeKey) -> bevy_input::keyboard::Nativ
 }
 
 pub fn convert_cursor_icon(cursor_icon: CursorIcon) -> winit::window::CursorIcon {
+    // No wildcard, so new Bevy variants fail to compile instead of silently mapping to `Default`.
     match cursor_icon {
+        CursorIcon::Default => winit::window::CursorIcon::Default,
         CursorIcon::Crosshair => winit::window::CursorIcon::Crosshair,
         CursorIcon::Pointer => winit::window::CursorIcon::Pointer,
         CursorIcon::Move => winit::window::CursorIcon::Move,
@@ -662,10 +670,21 @@ pub fn convert_cursor_icon(cursor_icon: CursorIcon) -> winit::window::CursorIcon
         CursorIcon::NwseResize => winit::window::CursorIcon::NwseResize,
         CursorIcon::ColResize => winit::window::CursorIcon::ColResize,
         CursorIcon::RowResize => winit::window::CursorIcon::RowResize,
-        _ => winit::window::CursorIcon::Default,
     }
 }
 
+pub fn convert_cursor_grab_mode(grab_mode: CursorGrabMode) -> winit::window::CursorGrabMode {
+    match grab_mode {
+        CursorGrabMode::None => winit::window::CursorGrabMode::None,
+        CursorGrabMode::Confined => winit::window::CursorGrabMode::Confined,
+        CursorGrabMode::Locked => winit::window::CursorGrabMode::Locked,
+    }
+}
+
+pub fn convert_window_icon(icon: &WindowIcon) -> Result<winit::window::Icon, BadIcon> {
+    winit::window::Icon::from_rgba(icon.rgba.clone(), icon.width, icon.height)
+}
+
 pub fn convert_window_level(window_level: WindowLevel) -> winit::window::WindowLevel {
     match window_level {
         WindowLevel::AlwaysOnBottom => winit::window::WindowLevel::AlwaysOnBottom,
@@ -701,3 +720,175 @@ pub fn convert_enabled_buttons(enabled_buttons: EnabledButtons) -> winit::window
     }
     window_buttons
 }
+
+pub fn convert_winit_enabled_buttons(
+    window_buttons: winit::window::WindowButtons,
+) -> EnabledButtons {
+    EnabledButtons {
+        minimize: window_buttons.contains(winit::window::WindowButtons::MINIMIZE),
+        maximize: window_buttons.contains(winit::window::WindowButtons::MAXIMIZE),
+        close: window_buttons.contains(winit::window::WindowButtons::CLOSE),
+    }
+}
+
+/// Converts a position in physical pixels to `winit`'s representation.
+pub fn to_physical_position(position: IVec2) -> PhysicalPosition<i32> {
+    PhysicalPosition::new(position.x, position.y)
+}
+
+/// Converts a position in physical pixels from `winit`'s representation.
+pub fn from_physical_position(position: PhysicalPosition<i32>) -> IVec2 {
+    IVec2::new(position.x, position.y)
+}
+
+/// Converts a position in logical pixels to `winit`'s representation.
+pub fn to_logical_position(position: Vec2) -> LogicalPosition<f32> {
+    LogicalPosition::new(position.x, position.y)
+}
+
+/// Converts a size in physical pixels to `winit`'s representation.
+pub fn to_physical_size(size: UVec2) -> PhysicalSize<u32> {
+    PhysicalSize::new(size.x, size.y)
+}
+
+/// Converts a size in physical pixels from `winit`'s representation.
+pub fn from_physical_size(size: PhysicalSize<u32>) -> UVec2 {
+    UVec2::new(size.width, size.height)
+}
+
+/// Converts a size in logical pixels to `winit`'s representation.
+pub fn to_logical_size(size: Vec2) -> LogicalSize<f32> {
+    LogicalSize::new(size.x, size.y)
+}
+
+/// Converts a text caret to the arguments of `winit`'s
+/// [`set_ime_cursor_area`](winit::window::Window::set_ime_cursor_area).
+///
+/// `caret_position` is the top of the caret and `caret_height` its height, both in logical pixels
+/// relative to the top-left of the client area. The area is one logical pixel wide, and its
+/// physical size is rounded up so it always covers the whole caret, and at least one pixel, at
+/// `scale_factor`.
+pub fn ime_area(
+    caret_position: Vec2,
+    caret_height: f32,
+    scale_factor: f64,
+) -> (LogicalPosition<f64>, PhysicalSize<u32>) {
+    let position = LogicalPosition::new(caret_position.x as f64, caret_position.y as f64);
+    let to_physical = |logical: f64| ((logical * scale_factor).ceil() as u32).max(1);
+    let size = PhysicalSize::new(to_physical(1.0), to_physical(caret_height.max(0.0) as f64));
+    (position, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ime_area_covers_the_caret_at_any_scale_factor() {
+        let caret = Vec2::new(12.5, 40.0);
+        for (scale_factor, width, height) in [
+            (1.0, 1, 10),
+            (1.25, 2, 13),
+            (1.5, 2, 15),
+            (2.0, 2, 20),
+            (0.5, 1, 5),
+        ] {
+            let (position, size) = ime_area(caret, 10.0, scale_factor);
+            assert_eq!(position, LogicalPosition::new(12.5, 40.0));
+            assert_eq!(size, PhysicalSize::new(width, height), "at {scale_factor}");
+        }
+
+        // Degenerate carets still get an area of at least one pixel.
+        let (_, size) = ime_area(caret, -3.0, 0.1);
+        assert_eq!(size, PhysicalSize::new(1, 1));
+    }
+
+    #[test]
+    fn dpi_conversions_round_trip() {
+        for position in [
+            IVec2::ZERO,
+            IVec2::new(-1920, 40),
+            IVec2::new(i32::MAX, i32::MIN),
+        ] {
+            assert_eq!(
+                from_physical_position(to_physical_position(position)),
+                position
+            );
+        }
+        for size in [UVec2::ZERO, UVec2::new(1280, 720), UVec2::splat(u32::MAX)] {
+            assert_eq!(from_physical_size(to_physical_size(size)), size);
+        }
+
+        let position = to_logical_position(Vec2::new(-12.5, 0.25));
+        assert_eq!((position.x, position.y), (-12.5, 0.25));
+        let size = to_logical_size(Vec2::new(800.5, 600.));
+        assert_eq!((size.width, size.height), (800.5, 600.));
+    }
+
+    #[test]
+    fn cursor_icons_map_to_the_matching_winit_icon() {
+        // Every variant, so a new one has to be added here too.
+        let icons = [
+            CursorIcon::Default,
+            CursorIcon::Crosshair,
+            CursorIcon::Pointer,
+            CursorIcon::Move,
+            CursorIcon::Text,
+            CursorIcon::Wait,
+            CursorIcon::Help,
+            CursorIcon::Progress,
+            CursorIcon::NotAllowed,
+            CursorIcon::ContextMenu,
+            CursorIcon::Cell,
+            CursorIcon::VerticalText,
+            CursorIcon::Alias,
+            CursorIcon::Copy,
+            CursorIcon::NoDrop,
+            CursorIcon::Grab,
+            CursorIcon::Grabbing,
+            CursorIcon::AllScroll,
+            CursorIcon::ZoomIn,
+            CursorIcon::ZoomOut,
+            CursorIcon::EResize,
+            CursorIcon::NResize,
+            CursorIcon::NeResize,
+            CursorIcon::NwResize,
+            CursorIcon::SResize,
+            CursorIcon::SeResize,
+            CursorIcon::SwResize,
+            CursorIcon::WResize,
+            CursorIcon::EwResize,
+            CursorIcon::NsResize,
+            CursorIcon::NeswResize,
+            CursorIcon::NwseResize,
+            CursorIcon::ColResize,
+            CursorIcon::RowResize,
+        ];
+        for icon in icons {
+            // Both follow the CSS cursor names, e.g. `NotAllowed` is `not-allowed`.
+            let mut css_name = String::new();
+            for (i, c) in format!("{icon:?}").chars().enumerate() {
+                if c.is_uppercase() && i > 0 {
+                    css_name.push('-');
+                }
+                css_name.push(c.to_ascii_lowercase());
+            }
+            assert_eq!(convert_cursor_icon(icon).name(), css_name);
+        }
+    }
+
+    #[test]
+    fn enabled_buttons_round_trip() {
+        for bits in 0..8 {
+            let enabled_buttons = EnabledButtons {
+                minimize: bits & 1 != 0,
+                maximize: bits & 2 != 0,
+                close: bits & 4 != 0,
+            };
+            assert_eq!(
+                convert_winit_enabled_buttons(convert_enabled_buttons(enabled_buttons)),
+                enabled_buttons
+            );
+        }
+    }
+}
@@ -7,21 +7,32 @@
 
 pub mod accessibility;
 mod converters;
+pub mod platform;
 mod system;
+mod winit_components;
 mod winit_config;
+mod winit_events;
 mod winit_hook;
 mod winit_windows;
 
 use approx::relative_eq;
 use bevy_a11y::AccessibilityRequested;
 use bevy_utils::{Duration, Instant};
-use system::{changed_hooks, changed_windows, create_windows, despawn_windows, CachedWindow};
+pub use converters::{
+    from_physical_position, from_physical_size, ime_area, to_logical_position, to_logical_size,
+    to_physical_position, to_physical_size,
+};
+pub use system::Cached;
+use system::{close_requested_windows, create_windows, despawn_windows, screenshot_requests};
 use winit::dpi::{LogicalSize, PhysicalSize};
+pub use winit_components::*;
 pub use winit_config::*;
+pub use winit_events::*;
 pub use winit_hook::*;
 pub use winit_windows::*;
 
 use bevy_app::{App, AppExit, Last, Plugin, PluginsState};
+use bevy_ecs::entity::EntityHashMap;
 use bevy_ecs::event::{Events, ManualEventReader};
 use bevy_ecs::prelude::*;
 use bevy_ecs::system::SystemState;
@@ -34,20 +45,17 @@ use bevy_math::{ivec2, DVec2, Vec2};
 use bevy_tasks::tick_global_task_pools_on_main_thread;
 use bevy_utils::tracing::{error, trace, warn};
 use bevy_window::{
-    exit_on_all_closed, ApplicationLifetime, CursorEntered, CursorLeft, CursorMoved,
-    FileDragAndDrop, Ime, ReceivedCharacter, RequestRedraw, Window,
-    WindowBackendScaleFactorChanged, WindowCloseRequested, WindowCreated, WindowDestroyed,
-    WindowFocused, WindowMoved, WindowOccluded, WindowResized, WindowScaleFactorChanged,
-    WindowThemeChanged,
+    ApplicationLifetime, Cursor, CursorEntered, CursorLeft, CursorMoved, FileDragAndDrop, Ime,
+    RawHandleWrapper, ReceivedCharacter, RequestRedraw, Window, WindowBackendScaleFactorChanged,
+    WindowCloseRequested, WindowClosed, WindowCreated, WindowDestroyed, WindowFocused, WindowMoved,
+    WindowOccluded, WindowResized, WindowScaleFactorChanged, WindowThemeChanged,
 };
-#[cfg(target_os = "android")]
-use bevy_window::{PrimaryWindow, RawHandleWrapper};
 
 #[cfg(target_os = "android")]
 pub use winit::platform::android::activity as android_activity;
 
 use winit::{
-    event::{self, DeviceEvent, Event, WindowEvent},
+    event::{self, DeviceEvent, Event, InnerSizeWriter, WindowEvent},
     event_loop::{ControlFlow, EventLoop, EventLoopBuilder, EventLoopWindowTarget},
 };
 
@@ -129,18 +137,25 @@ impl<T: WindowHook> Plugin for HookedWinitPlugin<T> {
 
         app.init_non_send_resource::<WinitWindows>()
             .init_resource::<WinitSettings>()
-            .set_runner(winit_runner::<T>)
+            .init_resource::<WinitWindowCommands>()
+            .init_resource::<CloseBehavior>()
+            .init_resource::<WinitEventHooks>()
+            .add_event::<WindowMinimized>()
+            .add_event::<WindowMaximized>()
+            .add_event::<WindowBackendResized>()
+            .add_event::<WindowBackendCreated>()
+            .add_event::<WindowRefreshRateChanged>()
+            .add_event::<RequestScreenshot>()
+            .add_event::<WindowCreationFailed>()
             .add_systems(
                 Last,
                 (
-                    // `exit_on_all_closed` only checks if windows exist but doesn't access data,
-                    // so we don't need to care about its ordering relative to `changed_windows`
-                    changed_windows.ambiguous_with(exit_on_all_closed),
-                    changed_hooks::<T>,
-                    despawn_windows,
-                )
-                    .chain(),
-            );
+                    close_requested_windows.before(despawn_windows::<T>),
+                    screenshot_requests,
+                ),
+            )
+            .set_runner(winit_runner::<T>)
+            .add_plugins(WindowHookPlugin::<T>::new());
 
         app.add_plugins(AccessKitPlugin);
 
@@ -159,7 +174,8 @@ impl<T: WindowHook> Plugin for HookedWinitPlugin<T> {
             // Otherwise, we want to create a window before `bevy_render` initializes the renderer
             // so that we have a surface to use as a hint. This improves compatibility with `wgpu`
             // backends, especially WASM/WebGL2.
-            let mut create_window = SystemState::<CreateWindowParams>::from_world(&mut app.world);
+            let mut create_window =
+                SystemState::<CreateWindowParams<T>>::from_world(&mut app.world);
             create_windows(&event_loop, create_window.get_mut(&mut app.world));
             create_window.apply(&mut app.world);
         }
@@ -198,6 +214,8 @@ struct WinitAppRunnerState {
     scheduled_update: Option<Instant>,
     /// Number of "forced" updates to trigger on application start
     startup_forced_updates: u32,
+    /// Scale factor changes waiting to settle, with the time they were received.
+    pending_scale_factors: EntityHashMap<(f64, Instant)>,
 }
 
 impl WinitAppRunnerState {
@@ -239,18 +257,44 @@ impl Default for WinitAppRunnerState {
             scheduled_update: None,
             // 3 seems to be enough, 5 is a safe margin
             startup_forced_updates: 5,
+            pending_scale_factors: EntityHashMap::default(),
         }
     }
 }
 
 type CreateWindowParams<'w, 's, T = NoHook, F = ()> = (
     Commands<'w, 's>,
-    Query<'w, 's, (Entity, &'static mut Window, Option<&'static T>), F>,
+    Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static mut Window,
+            Option<&'static T>,
+            Option<&'static WindowExtras>,
+            Has<RawHandleWrapper>,
+            Option<&'static CreationOrder>,
+            Has<QuietWindow>,
+        ),
+        F,
+    >,
     EventWriter<'w, WindowCreated>,
+    EventWriter<'w, WindowResized>,
+    EventWriter<'w, WindowBackendCreated>,
+    EventWriter<'w, WindowCreationFailed>,
     NonSendMut<'w, WinitWindows>,
     NonSendMut<'w, AccessKitAdapters>,
     ResMut<'w, WinitActionHandlers>,
     Res<'w, AccessibilityRequested>,
+    Res<'w, WinitSettings>,
+);
+
+type WindowEventParams<'w, 's, T> = (
+    EventWriter<'w, WindowResized>,
+    NonSend<'w, WinitWindows>,
+    Query<'w, 's, (&'static mut Window, &'static mut Cached<Window>)>,
+    NonSend<'w, AccessKitAdapters>,
+    Query<'w, 's, &'static mut T>,
 );
 
 /// The default [`App::runner`] for the [`WinitPlugin`] plugin.
@@ -280,15 +324,11 @@ pub fn winit_runner<T: WindowHook>(mut app: App) {
     let mut focused_windows_state: SystemState<(Res<WinitSettings>, Query<&Window>)> =
         SystemState::new(&mut app.world);
 
-    let mut event_writer_system_state: SystemState<(
-        EventWriter<WindowResized>,
-        NonSend<WinitWindows>,
-        Query<(&mut Window, &mut CachedWindow)>,
-        NonSend<AccessKitAdapters>,
-    )> = SystemState::new(&mut app.world);
+    let mut event_writer_system_state: SystemState<WindowEventParams<T>> =
+        SystemState::new(&mut app.world);
 
     let mut create_window =
-        SystemState::<CreateWindowParams<T, Added<Window>>>::from_world(&mut app.world);
+        SystemState::<CreateWindowParams<T, Without<Cached<Window>>>>::from_world(&mut app.world);
     // set up the event loop
     let event_handler = move |event, event_loop: &EventLoopWindowTarget<()>| {
         handle_winit_event(
@@ -316,13 +356,8 @@ fn handle_winit_event<T: WindowHook>(
     app: &mut App,
     app_exit_event_reader: &mut ManualEventReader<AppExit>,
     runner_state: &mut WinitAppRunnerState,
-    create_window: &mut SystemState<CreateWindowParams<T, Added<Window>>>,
-    event_writer_system_state: &mut SystemState<(
-        EventWriter<WindowResized>,
-        NonSend<WinitWindows>,
-        Query<(&mut Window, &mut CachedWindow)>,
-        NonSend<AccessKitAdapters>,
-    )>,
+    create_window: &mut SystemState<CreateWindowParams<T, Without<Cached<Window>>>>,
+    event_writer_system_state: &mut SystemState<WindowEventParams<T>>,
     focused_windows_state: &mut SystemState<(Res<WinitSettings>, Query<&Window>)>,
     redraw_event_reader: &mut ManualEventReader<RequestRedraw>,
     event: Event<()>,
@@ -349,8 +384,16 @@ fn handle_winit_event<T: WindowHook>(
         }
     }
 
+    if let Some(mut hooks) = app.world.get_resource_mut::<WinitEventHooks>() {
+        if hooks.pre_event(&event) == EventAction::Consume {
+            return;
+        }
+    }
+
     match event {
         Event::AboutToWait => {
+            apply_settled_scale_factors(app, runner_state);
+
             let (config, windows) = focused_windows_state.get(&app.world);
             let focused = windows.iter().any(|window| window.focused);
             let mut should_update = match config.update_mode(focused) {
@@ -385,7 +428,7 @@ fn handle_winit_event<T: WindowHook>(
 
             if should_update {
                 let visible = windows.iter().any(|window| window.visible);
-                let (_, winit_windows, _, _) = event_writer_system_state.get_mut(&mut app.world);
+                let (_, winit_windows, _, _, _) = event_writer_system_state.get_mut(&mut app.world);
                 if visible && runner_state.active != ActiveState::WillSuspend {
                     for window in winit_windows.windows.values() {
                         window.request_redraw();
@@ -418,7 +461,11 @@ fn handle_winit_event<T: WindowHook>(
         Event::WindowEvent {
             event, window_id, ..
         } => {
-            let (mut window_resized, winit_windows, mut windows, access_kit_adapters) =
+            let scale_factor_settle_time = app
+                .world
+                .resource::<WinitSettings>()
+                .scale_factor_settle_time;
+            let (mut window_resized, winit_windows, mut windows, access_kit_adapters, mut hooks) =
                 event_writer_system_state.get_mut(&mut app.world);
 
             let Some(window) = winit_windows.get_window_entity(window_id) else {
@@ -430,6 +477,7 @@ fn handle_winit_event<T: WindowHook>(
                 warn!("Window {window:?} is missing `Window` component, skipping event {event:?}");
                 return;
             };
+            let mut regrab = None;
 
             // Allow AccessKit to respond to `WindowEvent`s before they reach
             // the engine.
@@ -439,10 +487,33 @@ fn handle_winit_event<T: WindowHook>(
                 }
             }
 
+            if let (Ok(mut hook), Some(winit_window)) =
+                (hooks.get_mut(window), winit_windows.get_window(window))
+            {
+                hook.bypass_change_detection()
+                    .on_window_event(window, winit_window, &event);
+            }
+
             runner_state.window_event_received = true;
 
             match event {
                 WindowEvent::Resized(size) => {
+                    let mut size = size;
+                    if let (Some(ratio), Some(winit_window)) = (
+                        winit_windows.aspect_ratio(window),
+                        winit_windows.get_window(window),
+                    ) {
+                        let previous = PhysicalSize::new(
+                            win.resolution.physical_width(),
+                            win.resolution.physical_height(),
+                        );
+                        let fitted = crate::winit_windows::fit_aspect_ratio(size, previous, ratio);
+                        if fitted != size {
+                            // Platforms that resize asynchronously send another `Resized` once
+                            // the corrected size is applied.
+                            size = winit_window.request_inner_size(fitted).unwrap_or(size);
+                        }
+                    }
                     react_to_resize(&mut win, size, &mut window_resized, window);
                 }
                 WindowEvent::CloseRequested => app.send_event(WindowCloseRequested { window }),
@@ -520,56 +591,24 @@ fn handle_winit_event<T: WindowHook>(
                     scale_factor,
                     mut inner_size_writer,
                 } => {
-                    let prior_factor = win.resolution.scale_factor();
-                    win.resolution.set_scale_factor(scale_factor as f32);
-                    // Note: this may be different from new_scale_factor if
-                    // `scale_factor_override` is set to Some(thing)
-                    let new_factor = win.resolution.scale_factor();
-
-                    let mut new_inner_size =
-                        PhysicalSize::new(win.physical_width(), win.physical_height());
-                    let scale_factor_override = win.resolution.scale_factor_override();
-                    if let Some(forced_factor) = scale_factor_override {
-                        // This window is overriding the OS-suggested DPI, so its physical size
-                        // should be set based on the overriding value. Its logical size already
-                        // incorporates any resize constraints.
-                        let maybe_new_inner_size = LogicalSize::new(win.width(), win.height())
-                            .to_physical::<u32>(forced_factor as f64);
-                        if let Err(err) = inner_size_writer.request_inner_size(new_inner_size) {
-                            warn!("Winit Failed to resize the window: {err}");
-                        } else {
-                            new_inner_size = maybe_new_inner_size;
-                        }
-                    }
-                    let new_logical_width = new_inner_size.width as f32 / new_factor;
-                    let new_logical_height = new_inner_size.height as f32 / new_factor;
-
-                    let width_equal = relative_eq!(win.width(), new_logical_width);
-                    let height_equal = relative_eq!(win.height(), new_logical_height);
-                    win.resolution
-                        .set_physical_resolution(new_inner_size.width, new_inner_size.height);
-
-                    app.send_event(WindowBackendScaleFactorChanged {
-                        window,
-                        scale_factor,
-                    });
-                    if scale_factor_override.is_none() && !relative_eq!(new_factor, prior_factor) {
-                        app.send_event(WindowScaleFactorChanged {
+                    let scale_factor = crate::winit_windows::sanitize_scale_factor(scale_factor);
+                    if scale_factor_settle_time.is_zero() {
+                        react_to_scale_factor_change(
+                            app,
                             window,
                             scale_factor,
-                        });
-                    }
-
-                    if !width_equal || !height_equal {
-                        app.send_event(WindowResized {
-                            window,
-                            width: new_logical_width,
-                            height: new_logical_height,
-                        });
+                            Some(&mut inner_size_writer),
+                        );
+                    } else {
+                        // Applied in `apply_settled_scale_factors` once no other change arrives.
+                        runner_state
+                            .pending_scale_factors
+                            .insert(window, (scale_factor, Instant::now()));
                     }
                 }
                 WindowEvent::Focused(focused) => {
                     win.focused = focused;
+                    regrab = crate::winit_windows::regrab_on_focus(focused, win.cursor.grab_mode);
                     app.send_event(WindowFocused { window, focused });
                 }
                 WindowEvent::Occluded(occluded) => {
@@ -585,9 +624,7 @@ fn handle_winit_event<T: WindowHook>(
                     app.send_event(FileDragAndDrop::HoveredFileCanceled { window });
                 }
                 WindowEvent::Moved(position) => {
-                    let position = ivec2(position.x, position.y);
-                    win.position.set(position);
-                    app.send_event(WindowMoved { window, position });
+                    react_to_move(app, window, position);
                 }
                 WindowEvent::Ime(event) => match event {
                     event::Ime::Preedit(value, cursor) => {
@@ -608,6 +645,12 @@ fn handle_winit_event<T: WindowHook>(
                     }
                 },
                 WindowEvent::ThemeChanged(theme) => {
+                    if let Some(mut effective_theme) =
+                        app.world.get_mut::<EffectiveWindowTheme>(window)
+                    {
+                        effective_theme
+                            .set_if_neq(EffectiveWindowTheme(Some(convert_winit_theme(theme))));
+                    }
                     app.send_event(WindowThemeChanged {
                         window,
                         theme: convert_winit_theme(theme),
@@ -615,6 +658,7 @@ fn handle_winit_event<T: WindowHook>(
                 }
                 WindowEvent::Destroyed => {
                     app.send_event(WindowDestroyed { window });
+                    react_to_lost_window::<T>(app, window, window_id);
                 }
                 WindowEvent::RedrawRequested => {
                     run_app_update_if_should(
@@ -630,10 +674,19 @@ fn handle_winit_event<T: WindowHook>(
                 _ => {}
             }
 
-            let mut windows = app.world.query::<(&mut Window, &mut CachedWindow)>();
+            if let Some(grab_mode) = regrab {
+                // Synced back to the `Window` by `applied_cursor_changes`, like any other grab
+                // applied through `WinitWindows`.
+                app.world
+                    .non_send_resource_mut::<WinitWindows>()
+                    .set_cursor_grab(window, grab_mode);
+            }
+
+            let batching = app.world.non_send_resource::<WinitWindows>().batching;
+            let mut windows = app.world.query::<(&mut Window, &mut Cached<Window>)>();
             if let Ok((window_component, mut cache)) = windows.get_mut(&mut app.world, window) {
                 if window_component.is_changed() {
-                    cache.window = window_component.clone();
+                    sync_runner_changes(&window_component, &mut cache, batching);
                 }
             }
         }
@@ -671,34 +724,76 @@ fn handle_winit_event<T: WindowHook>(
                 // handle wrapper removed when the app was suspended.
                 let mut query = app
                         .world
-                        .query_filtered::<(Entity, &Window), (With<CachedWindow>, Without<bevy_window::RawHandleWrapper>)>();
-                if let Ok((entity, window)) = query.get_single(&app.world) {
+                        .query_filtered::<(Entity, &Window), (With<Cached<Window>>, Without<bevy_window::RawHandleWrapper>)>();
+                let suspended: Vec<_> = query
+                    .iter(&app.world)
+                    .map(|(entity, window)| {
+                        let hook = app.world.get::<T>(entity).cloned();
+                        let extras = app.world.get::<WindowExtras>(entity).cloned();
+                        (entity, window.clone(), hook, extras)
+                    })
+                    .collect();
+                for (entity, mut window, hook, extras) in suspended {
                     use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
-                    let window = window.clone();
 
                     let (
-                        ..,
+                        _,
+                        _,
+                        _,
+                        _,
+                        mut backend_created_events,
+                        mut creation_failed_events,
                         mut winit_windows,
                         mut adapters,
                         mut handlers,
                         accessibility_requested,
+                        _,
                     ) = create_window.get_mut(&mut app.world);
 
-                    let winit_window = winit_windows.create_window(
+                    let winit_window = match winit_windows.try_create_window(
                         event_loop,
                         entity,
-                        &window,
+                        &mut window,
+                        hook.as_ref(),
+                        extras.as_ref(),
                         &mut adapters,
                         &mut handlers,
                         &accessibility_requested,
-                    );
+                    ) {
+                        Ok(winit_window) => winit_window,
+                        Err(err) => {
+                            error!(
+                                "Failed to recreate window {:?} ({:?}) on resume: {}",
+                                window.title.as_str(),
+                                entity,
+                                err
+                            );
+                            creation_failed_events.send(WindowCreationFailed {
+                                window: entity,
+                                error: err.to_string(),
+                            });
+                            continue;
+                        }
+                    };
 
                     let wrapper = RawHandleWrapper {
                         window_handle: winit_window.window_handle().unwrap().as_raw(),
                         display_handle: winit_window.display_handle().unwrap().as_raw(),
                     };
+                    // The native surface is new, so renderers need to recreate theirs as well.
+                    backend_created_events.send(WindowBackendCreated {
+                        window: entity,
+                        window_id: winit_window.id(),
+                        scale_factor: winit_window.scale_factor(),
+                    });
 
-                    app.world.entity_mut(entity).insert(wrapper);
+                    // Creation may have changed the window, e.g. with a fallback grab mode.
+                    app.world.entity_mut(entity).insert((
+                        wrapper,
+                        Cached(window.clone()),
+                        Cached(window.cursor),
+                        window,
+                    ));
                 }
                 event_loop.set_control_flow(ControlFlow::Wait);
             }
@@ -712,7 +807,7 @@ fn run_app_update_if_should<T: WindowHook>(
     app: &mut App,
     focused_windows_state: &mut SystemState<(Res<WinitSettings>, Query<&Window>)>,
     event_loop: &EventLoopWindowTarget<()>,
-    create_window: &mut SystemState<CreateWindowParams<T, Added<Window>>>,
+    create_window: &mut SystemState<CreateWindowParams<T, Without<Cached<Window>>>>,
     app_exit_event_reader: &mut ManualEventReader<AppExit>,
     redraw_event_reader: &mut ManualEventReader<RequestRedraw>,
 ) {
@@ -725,11 +820,13 @@ fn run_app_update_if_should<T: WindowHook>(
         runner_state.active = ActiveState::Suspended;
         #[cfg(target_os = "android")]
         {
-            // Remove the `RawHandleWrapper` from the primary window.
+            // Remove the `RawHandleWrapper` from the windows.
             // This will trigger the surface destruction.
-            let mut query = app.world.query_filtered::<Entity, With<PrimaryWindow>>();
-            let entity = query.single(&app.world);
-            app.world.entity_mut(entity).remove::<RawHandleWrapper>();
+            let mut query = app.world.query_filtered::<Entity, With<RawHandleWrapper>>();
+            let entities: Vec<_> = query.iter(&app.world).collect();
+            for entity in entities {
+                app.world.entity_mut(entity).remove::<RawHandleWrapper>();
+            }
             event_loop.set_control_flow(ControlFlow::Wait);
         }
     }
@@ -779,6 +876,259 @@ fn run_app_update_if_should<T: WindowHook>(
     // (even if app did not update, some may have been created by plugin setup)
     create_windows(event_loop, create_window.get_mut(&mut app.world));
     create_window.apply(&mut app.world);
+
+    // Reactive apps may not be woken up by anything else when a failed window creation is due for
+    // a retry.
+    let settings = app.world.resource::<WinitSettings>();
+    let next_retry = app
+        .world
+        .non_send_resource::<WinitWindows>()
+        .next_creation_retry(
+            settings.window_creation_retries,
+            settings.window_creation_retry_delay,
+        );
+    if let Some(next_retry) = next_retry {
+        let scheduled_earlier =
+            matches!(runner_state.scheduled_update, Some(next) if next <= next_retry);
+        if !runner_state.redraw_requested && !scheduled_earlier {
+            runner_state.scheduled_update = Some(next_retry);
+            event_loop.set_control_flow(ControlFlow::WaitUntil(next_retry));
+        }
+    }
+}
+
+/// Records that the platform moved a window, e.g. because the user dragged it.
+///
+/// The cache is synced after the event like for any other change the runner makes, so
+/// `changed_windows` doesn't move the window back.
+fn react_to_move(app: &mut App, window: Entity, position: winit::dpi::PhysicalPosition<i32>) {
+    let position = ivec2(position.x, position.y);
+    if let Some(mut win) = app.world.get_mut::<Window>(window) {
+        win.position.set(position);
+    }
+    app.send_event(WindowMoved { window, position });
+}
+
+/// Forgets a winit window that was destroyed while its [`Window`] still exists.
+///
+/// The window is created again if [`WinitSettings::recreate_lost_windows`] is enabled, otherwise
+/// the [`Window`] is removed and the window closes as usual.
+fn react_to_lost_window<T: WindowHook>(
+    app: &mut App,
+    window: Entity,
+    window_id: winit::window::WindowId,
+) {
+    // Ids of removed windows still resolve to their entity, which may have a new window by now.
+    let winit_windows = app.world.non_send_resource::<WinitWindows>();
+    if winit_windows.entity_to_winit.get(&window) != Some(&window_id) {
+        return;
+    }
+    // The `Window` still exists, so the platform destroyed the window on its own.
+    warn!("The winit window of {window:?} was destroyed unexpectedly");
+    app.world
+        .non_send_resource_mut::<WinitWindows>()
+        .remove_window(window);
+
+    // Without its caches the entity is picked up by `create_windows` again.
+    let recreate = app.world.resource::<WinitSettings>().recreate_lost_windows;
+    app.world.entity_mut(window).remove::<(
+        RawHandleWrapper,
+        Cached<Window>,
+        Cached<Cursor>,
+        Cached<WindowExtras>,
+        Cached<T>,
+    )>();
+    if recreate {
+        app.send_event(WindowClosed { window });
+    } else {
+        // `despawn_windows` sends the `WindowClosed` event.
+        app.world.entity_mut(window).remove::<Window>();
+    }
+}
+
+/// Removes the scale factors that were received at least `settle_time` before `now` from
+/// `pending`, returning them.
+///
+/// Each window only keeps its latest scale factor, so a window whose scale factor keeps changing
+/// only settles on the final one.
+fn take_settled_scale_factors(
+    pending: &mut EntityHashMap<(f64, Instant)>,
+    settle_time: Duration,
+    now: Instant,
+) -> Vec<(Entity, f64)> {
+    let mut settled = Vec::new();
+    pending.retain(|&window, &mut (scale_factor, received)| {
+        if now.saturating_duration_since(received) < settle_time {
+            return true;
+        }
+        settled.push((window, scale_factor));
+        false
+    });
+    settled
+}
+
+/// Applies the scale factor changes that didn't change again for
+/// [`WinitSettings::scale_factor_settle_time`].
+fn apply_settled_scale_factors(app: &mut App, runner_state: &mut WinitAppRunnerState) {
+    if runner_state.pending_scale_factors.is_empty() {
+        return;
+    }
+    let settle_time = app
+        .world
+        .resource::<WinitSettings>()
+        .scale_factor_settle_time;
+
+    let settled = take_settled_scale_factors(
+        &mut runner_state.pending_scale_factors,
+        settle_time,
+        Instant::now(),
+    );
+    for (window, scale_factor) in settled {
+        // The scale factor went back to where it was, e.g. after briefly crossing a monitor edge.
+        let unchanged = match app.world.get::<Window>(window) {
+            Some(win) => relative_eq!(win.resolution.base_scale_factor(), scale_factor as f32),
+            None => true,
+        };
+        if unchanged {
+            continue;
+        }
+        react_to_scale_factor_change(app, window, scale_factor, None);
+        runner_state.window_event_received = true;
+    }
+
+    // Keep updating until the remaining changes have settled.
+    if !runner_state.pending_scale_factors.is_empty() {
+        runner_state.redraw_requested = true;
+    }
+}
+
+/// Applies a new scale factor to a window, requesting a new inner size if the window overrides
+/// its scale factor.
+///
+/// `inner_size_writer` is only available while handling the
+/// [`ScaleFactorChanged`](WindowEvent::ScaleFactorChanged) event, the size is requested from the
+/// winit window otherwise.
+fn react_to_scale_factor_change(
+    app: &mut App,
+    window: Entity,
+    scale_factor: f64,
+    inner_size_writer: Option<&mut InnerSizeWriter>,
+) {
+    let physical_constraints = app
+        .world
+        .get::<WindowExtras>(window)
+        .is_some_and(|extras| extras.physical_resize_constraints);
+    let Some(mut win) = app.world.get_mut::<Window>(window) else {
+        return;
+    };
+    let prior_factor = win.resolution.scale_factor();
+    let prior_size = LogicalSize::new(win.width(), win.height());
+    win.resolution.set_scale_factor(scale_factor as f32);
+    // Note: this may be different from new_scale_factor if
+    // `scale_factor_override` is set to Some(thing)
+    let new_factor = win.resolution.scale_factor();
+
+    let mut new_inner_size = PhysicalSize::new(win.physical_width(), win.physical_height());
+    let scale_factor_override = win.resolution.scale_factor_override();
+    let (width, height) = (win.width(), win.height());
+    // Non-resizable windows are pinned to their physical size, which has to follow the scale
+    // factor. The runner syncs the cache, so `changed_windows` won't pin them again.
+    let pinned_size = (!win.resizable).then(|| {
+        pinned_inner_size(
+            prior_size,
+            &win.resize_constraints,
+            new_factor as f64,
+            physical_constraints,
+        )
+    });
+    if let (Some(pinned_size), Some(winit_window)) = (
+        pinned_size,
+        app.world
+            .non_send_resource::<WinitWindows>()
+            .get_window(window),
+    ) {
+        pin_inner_size(winit_window, pinned_size);
+    }
+    let forced_size = match scale_factor_override {
+        // This window is overriding the OS-suggested DPI, so its physical size
+        // should be set based on the overriding value. Its logical size already
+        // incorporates any resize constraints.
+        Some(forced_factor) => Some(pinned_size.unwrap_or_else(|| {
+            LogicalSize::new(width, height).to_physical::<u32>(forced_factor as f64)
+        })),
+        None => pinned_size,
+    };
+    if let Some(maybe_new_inner_size) = forced_size {
+        let requested = match inner_size_writer {
+            Some(inner_size_writer) => inner_size_writer
+                .request_inner_size(maybe_new_inner_size)
+                .map_err(|err| warn!("Winit Failed to resize the window: {err}"))
+                .is_ok(),
+            None => {
+                if let Some(winit_window) = app
+                    .world
+                    .non_send_resource::<WinitWindows>()
+                    .get_window(window)
+                {
+                    // The size actually applied is reported by a `Resized` event.
+                    let _ = winit_window.request_inner_size(maybe_new_inner_size);
+                }
+                true
+            }
+        };
+        if requested {
+            new_inner_size = maybe_new_inner_size;
+        }
+    }
+    let new_logical_width = new_inner_size.width as f32 / new_factor;
+    let new_logical_height = new_inner_size.height as f32 / new_factor;
+
+    let width_equal = relative_eq!(width, new_logical_width);
+    let height_equal = relative_eq!(height, new_logical_height);
+    if let Some(mut win) = app.world.get_mut::<Window>(window) {
+        win.resolution
+            .set_physical_resolution(new_inner_size.width, new_inner_size.height);
+    }
+
+    app.send_event(WindowBackendScaleFactorChanged {
+        window,
+        scale_factor,
+    });
+    if scale_factor_override.is_none() && !relative_eq!(new_factor, prior_factor) {
+        app.send_event(WindowScaleFactorChanged {
+            window,
+            scale_factor,
+        });
+    }
+
+    if !width_equal || !height_equal {
+        app.send_event(WindowBackendResized {
+            window,
+            width: new_logical_width,
+            height: new_logical_height,
+            scale_factor,
+        });
+        app.send_event(WindowResized {
+            window,
+            width: new_logical_width,
+            height: new_logical_height,
+        });
+    }
+}
+
+/// Syncs the cache of a [`Window`] the runner changed while handling an event, so the change
+/// isn't applied back to the `winit` window.
+///
+/// While batching, only the fields the runner writes are synced, since the app's own changes
+/// since [`WinitWindows::begin_batch`] still need to be applied once the batch ends.
+fn sync_runner_changes(window: &Window, cache: &mut Cached<Window>, batching: bool) {
+    if batching {
+        cache.0.focused = window.focused;
+        cache.0.position = window.position;
+        cache.0.resolution = window.resolution.clone();
+    } else {
+        cache.0 = window.clone();
+    }
 }
 
 fn react_to_resize(
@@ -796,3 +1146,202 @@ fn react_to_resize(
         height: win.height(),
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_window::{WindowPosition, WindowResolution};
+
+    #[test]
+    fn batched_changes_survive_runner_cache_sync() {
+        let mut window = Window::default();
+        let mut cache = Cached(window.clone());
+        let mut winit_windows = WinitWindows::default();
+
+        winit_windows.begin_batch();
+        window.title = "Batched".to_string();
+        // The runner handles a `Focused` event in the middle of the batch.
+        window.focused = !window.focused;
+        sync_runner_changes(&window, &mut cache, winit_windows.is_batching());
+        winit_windows.end_batch();
+
+        assert_eq!(cache.focused, window.focused);
+        assert_ne!(cache.title, window.title);
+    }
+
+    #[test]
+    fn destroyed_events_of_stale_windows_are_ignored() {
+        let mut app = App::new();
+        app.insert_non_send_resource(WinitWindows::default())
+            .init_resource::<WinitSettings>()
+            .add_event::<WindowClosed>();
+        let window = app
+            .world
+            .spawn((Window::default(), Cached(Window::default())))
+            .id();
+
+        // SAFETY: the id is only compared, never passed to a platform API.
+        let stale_id = unsafe { winit::window::WindowId::dummy() };
+        react_to_lost_window::<NoHook>(&mut app, window, stale_id);
+
+        let entity = app.world.entity(window);
+        assert!(entity.contains::<Window>());
+        assert!(entity.contains::<Cached<Window>>());
+        assert!(app.world.resource::<Events<WindowClosed>>().is_empty());
+    }
+
+    #[test]
+    fn destroyed_tracked_windows_are_forgotten() {
+        // SAFETY: the id is only compared, never passed to a platform API.
+        let window_id = unsafe { winit::window::WindowId::dummy() };
+        for recreate_lost_windows in [true, false] {
+            let mut app = App::new();
+            let mut winit_windows = WinitWindows::default();
+            app.insert_resource(WinitSettings {
+                recreate_lost_windows,
+                ..Default::default()
+            })
+            .add_event::<WindowClosed>();
+            let window = app
+                .world
+                .spawn((Window::default(), Cached(Window::default())))
+                .id();
+            winit_windows.entity_to_winit.insert(window, window_id);
+            winit_windows.winit_to_entity.insert(window_id, window);
+            app.insert_non_send_resource(winit_windows);
+
+            react_to_lost_window::<NoHook>(&mut app, window, window_id);
+
+            let winit_windows = app.world.non_send_resource::<WinitWindows>();
+            assert!(!winit_windows.entity_to_winit.contains_key(&window));
+            let entity = app.world.entity(window);
+            assert!(!entity.contains::<Cached<Window>>());
+            // Without recreation, `despawn_windows` sends `WindowClosed` once `Window` is gone.
+            assert_eq!(entity.contains::<Window>(), recreate_lost_windows);
+            let closed = app.world.resource::<Events<WindowClosed>>();
+            assert_eq!(closed.len(), usize::from(recreate_lost_windows));
+        }
+    }
+
+    #[test]
+    fn moved_windows_update_their_position() {
+        let mut app = App::new();
+        app.insert_non_send_resource(WinitWindows::default())
+            .add_event::<WindowMoved>();
+        let window = app
+            .world
+            .spawn((Window::default(), Cached(Window::default())))
+            .id();
+
+        react_to_move(
+            &mut app,
+            window,
+            winit::dpi::PhysicalPosition::new(100, 200),
+        );
+
+        let position = ivec2(100, 200);
+        let moved: Vec<_> = app
+            .world
+            .resource_mut::<Events<WindowMoved>>()
+            .drain()
+            .collect();
+        assert_eq!(moved, [WindowMoved { window, position }]);
+        let mut windows = app.world.query::<(&Window, &mut Cached<Window>)>();
+        let (win, mut cache) = windows.get_mut(&mut app.world, window).unwrap();
+        assert_eq!(win.position, WindowPosition::At(position));
+        sync_runner_changes(win, &mut cache, false);
+        assert_eq!(cache.position, WindowPosition::At(position));
+    }
+
+    #[test]
+    fn scale_factor_overrides_keep_their_logical_size() {
+        let mut app = App::new();
+        app.insert_non_send_resource(WinitWindows::default())
+            .add_event::<WindowBackendScaleFactorChanged>()
+            .add_event::<WindowScaleFactorChanged>()
+            .add_event::<WindowBackendResized>()
+            .add_event::<WindowResized>();
+        let window = app
+            .world
+            .spawn(Window {
+                resolution: WindowResolution::new(800., 600.).with_scale_factor_override(1.),
+                ..Default::default()
+            })
+            .id();
+
+        react_to_scale_factor_change(&mut app, window, 2., None);
+
+        let window = app.world.get::<Window>(window).unwrap();
+        assert_eq!(window.resolution.base_scale_factor(), 2.);
+        assert_eq!((window.width(), window.height()), (800., 600.));
+        assert!(app.world.resource::<Events<WindowResized>>().is_empty());
+    }
+
+    #[test]
+    fn non_resizable_windows_keep_their_logical_size_on_scale_factor_changes() {
+        let mut app = App::new();
+        app.insert_non_send_resource(WinitWindows::default())
+            .add_event::<WindowBackendScaleFactorChanged>()
+            .add_event::<WindowScaleFactorChanged>()
+            .add_event::<WindowBackendResized>()
+            .add_event::<WindowResized>();
+        let window = app
+            .world
+            .spawn(Window {
+                resolution: WindowResolution::new(800., 600.),
+                resizable: false,
+                ..Default::default()
+            })
+            .id();
+
+        react_to_scale_factor_change(&mut app, window, 2., None);
+
+        let window = app.world.get::<Window>(window).unwrap();
+        assert_eq!(
+            (window.physical_width(), window.physical_height()),
+            (1600, 1200)
+        );
+        assert_eq!((window.width(), window.height()), (800., 600.));
+    }
+
+    #[test]
+    fn runner_cache_sync_copies_everything_outside_batches() {
+        let mut window = Window::default();
+        let mut cache = Cached(window.clone());
+
+        window.title = "Changed".to_string();
+        window.focused = !window.focused;
+        sync_runner_changes(&window, &mut cache, false);
+
+        assert_eq!(cache.focused, window.focused);
+        assert_eq!(cache.title, window.title);
+    }
+
+    #[test]
+    fn only_the_final_scale_factor_settles() {
+        let window = Entity::from_raw(1);
+        let settle_time = Duration::from_millis(100);
+        let start = Instant::now();
+        let mut pending = EntityHashMap::default();
+
+        // The window keeps crossing a monitor edge, as in the `ScaleFactorChanged` handler.
+        for (i, scale_factor) in [2.0, 1.0, 2.0, 1.5].into_iter().enumerate() {
+            let received = start + Duration::from_millis(20 * i as u64);
+            pending.insert(window, (scale_factor, received));
+            assert!(take_settled_scale_factors(&mut pending, settle_time, received).is_empty());
+        }
+
+        let last_received = start + Duration::from_millis(60);
+        assert!(take_settled_scale_factors(
+            &mut pending,
+            settle_time,
+            last_received + Duration::from_millis(99)
+        )
+        .is_empty());
+        assert_eq!(
+            take_settled_scale_factors(&mut pending, settle_time, last_received + settle_time),
+            vec![(window, 1.5)]
+        );
+        assert!(pending.is_empty());
+    }
+}
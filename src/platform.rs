@@ -0,0 +1,116 @@
+//! Helpers for common platform-specific [`WindowAttributes`] extensions.
+//!
+//! These are meant to be called from
+//! [`WindowHook::attributes_hook`](crate::WindowHook::attributes_hook).
+//! Each helper only exists on the platforms that support it, so hook authors only need a single
+//! `#[cfg]` around the call rather than importing `winit`'s extension traits per platform.
+
+use crate::WindowAttributes;
+
+/// Makes the title bar transparent and hides the title, extending the content view underneath it.
+///
+/// ## Platform-specific
+///
+/// Only available on macOS.
+#[cfg(target_os = "macos")]
+pub fn transparent_titlebar(attributes: WindowAttributes) -> WindowAttributes {
+    use winit::platform::macos::WindowBuilderExtMacOS;
+    attributes
+        .with_titlebar_transparent(true)
+        .with_title_hidden(true)
+        .with_fullsize_content_view(true)
+}
+
+/// Makes the title bar transparent, without hiding the title or changing the content view.
+///
+/// Combine with [`fullsize_content_view`] for content drawn underneath the title bar. `winit`
+/// can only set this at creation.
+///
+/// ## Platform-specific
+///
+/// Only available on macOS.
+#[cfg(target_os = "macos")]
+pub fn titlebar_transparent(attributes: WindowAttributes, transparent: bool) -> WindowAttributes {
+    use winit::platform::macos::WindowBuilderExtMacOS;
+    attributes.with_titlebar_transparent(transparent)
+}
+
+/// Extends the content view of the window underneath its title bar.
+///
+/// `winit` can only set this at creation.
+///
+/// ## Platform-specific
+///
+/// Only available on macOS.
+#[cfg(target_os = "macos")]
+pub fn fullsize_content_view(attributes: WindowAttributes, fullsize: bool) -> WindowAttributes {
+    use winit::platform::macos::WindowBuilderExtMacOS;
+    attributes.with_fullsize_content_view(fullsize)
+}
+
+/// Sets the identifier used to group windows into native tabs.
+///
+/// ## Platform-specific
+///
+/// Only available on macOS.
+#[cfg(target_os = "macos")]
+pub fn tabbing_identifier(attributes: WindowAttributes, identifier: &str) -> WindowAttributes {
+    use winit::platform::macos::WindowBuilderExtMacOS;
+    attributes.with_tabbing_identifier(identifier)
+}
+
+/// Hides the window from the taskbar.
+///
+/// ## Platform-specific
+///
+/// Only available on Windows.
+#[cfg(target_os = "windows")]
+pub fn skip_taskbar(attributes: WindowAttributes, skip: bool) -> WindowAttributes {
+    use winit::platform::windows::WindowBuilderExtWindows;
+    attributes.with_skip_taskbar(skip)
+}
+
+/// Shows or hides the drop shadow of an undecorated window.
+///
+/// ## Platform-specific
+///
+/// Only available on Windows.
+#[cfg(target_os = "windows")]
+pub fn undecorated_shadow(attributes: WindowAttributes, shadow: bool) -> WindowAttributes {
+    use winit::platform::windows::WindowBuilderExtWindows;
+    attributes.with_undecorated_shadow(shadow)
+}
+
+/// Creates the window with the override-redirect flag, so the window manager ignores it. This is
+/// useful for popups and overlays.
+///
+/// ## Platform-specific
+///
+/// Only available on Linux with the `x11` feature, and only has an effect on X11.
+#[cfg(all(target_os = "linux", feature = "x11"))]
+pub fn override_redirect(
+    attributes: WindowAttributes,
+    override_redirect: bool,
+) -> WindowAttributes {
+    use winit::platform::x11::WindowBuilderExtX11;
+    attributes.with_override_redirect(override_redirect)
+}
+
+/// Sets the application name of the window: `WM_CLASS` on X11, and the app id on Wayland.
+///
+/// ## Platform-specific
+///
+/// Only available on Linux with the `x11` or `wayland` feature.
+#[cfg(all(target_os = "linux", any(feature = "x11", feature = "wayland")))]
+pub fn app_name(
+    attributes: WindowAttributes,
+    general: impl Into<String>,
+    instance: impl Into<String>,
+) -> WindowAttributes {
+    // Both extension traits set the same name, so either one covers both backends.
+    #[cfg(not(feature = "x11"))]
+    use winit::platform::wayland::WindowBuilderExtWayland as WindowBuilderExt;
+    #[cfg(feature = "x11")]
+    use winit::platform::x11::WindowBuilderExtX11 as WindowBuilderExt;
+    WindowBuilderExt::with_name(attributes, general, instance)
+}
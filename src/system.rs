@@ -1,37 +1,70 @@
 use bevy_ecs::{
-    entity::Entity,
-    event::EventWriter,
-    prelude::{Changed, Component},
+    entity::{Entity, EntityHashMap},
+    event::{EventReader, EventWriter},
+    prelude::{Changed, Component, DetectChanges, DetectChangesMut, Mut},
     query::QueryFilter,
     removal_detection::RemovedComponents,
-    system::{NonSendMut, Query, SystemParamItem},
+    system::{Commands, EntityCommands, Local, NonSend, NonSendMut, Query, Res, SystemParamItem},
+};
+use bevy_utils::{
+    tracing::{error, field, info, info_span, trace, warn},
+    Instant,
 };
-use bevy_utils::tracing::{error, info, warn};
 use bevy_window::{
-    RawHandleWrapper, Window, WindowClosed, WindowCreated, WindowMode, WindowResized,
+    Cursor, CursorGrabMode, RawHandleWrapper, RequestRedraw, Window,
+    WindowBackendScaleFactorChanged, WindowCloseRequested, WindowClosed, WindowCreated, WindowMode,
+    WindowMoved, WindowResized, WindowResolution,
 };
 use std::fmt::{Debug, Formatter};
-use std::ops::{Deref, DerefMut};
+use std::ops::Deref;
 
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use winit::{
-    dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
+    dpi::{PhysicalPosition, PhysicalSize},
     event_loop::EventLoopWindowTarget,
 };
 
 use crate::{
     converters::{
         self, convert_enabled_buttons, convert_window_level, convert_window_theme,
-        convert_winit_theme,
+        convert_winit_enabled_buttons, convert_winit_theme, to_logical_size,
     },
     get_best_videomode, get_fitting_videomode,
     winit_hook::WindowHook,
-    CreateWindowParams, WinitWindows,
+    winit_windows::{
+        apply_resize_constraints, apply_window_state, attempt_grab, check_hit_test_passthrough,
+        clamp_to_constraints, clamp_to_monitors, creation_retry_delay,
+        exclusive_fullscreen_fallback, exclusive_videomode, fallback_window_position,
+        find_videomode, fit_aspect_ratio, pin_inner_size, sanitize_scale_factor,
+        warn_invalid_constraints, Deferral,
+    },
+    CloseBehavior, CreateWindowParams, CreationOrder, CurrentMonitor, CursorWarp,
+    EffectiveWindowTheme, ExclusiveVideoMode, RequestScreenshot, WindowBackendCreated,
+    WindowCreationFailed, WindowExtras, WindowMaximized, WindowMinimized, WindowRefreshRateChanged,
+    WinitSettings, WinitWindowCommands, WinitWindowState, WinitWindows,
 };
 
-/// The cached state of a component. Used to check which properties were changed from within the app.
-#[derive(Clone, Component)]
-pub(crate) struct Cached<T>(T);
+/// Logs at the `info` level, or at the `trace` level for [`QuietWindow`](crate::QuietWindow)s.
+macro_rules! info_unless_quiet {
+    ($quiet:expr, $($arg:tt)+) => {
+        if $quiet {
+            trace!($($arg)+);
+        } else {
+            info!($($arg)+);
+        }
+    };
+}
+
+/// The state of a component as last applied to the [`winit`] backend. Used to check which
+/// properties were changed from within the app.
+///
+/// Window entities get a `Cached<Window>`, a `Cached<Cursor>` for [`Window::cursor`], a
+/// `Cached<WindowExtras>`, and a cache of their [`WindowHook`] once their `winit` window is
+/// created. Comparing a cache to the live component shows which fields are still waiting to be
+/// applied, e.g. in debugging tools. Caches can only be read; they are kept up to date by this
+/// crate.
+#[derive(Clone, Component, PartialEq)]
+pub struct Cached<T>(pub(crate) T);
 
 impl<T> Deref for Cached<T> {
     type Target = T;
@@ -41,23 +74,61 @@ impl<T> Deref for Cached<T> {
     }
 }
 
-impl<T> DerefMut for Cached<T> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
-    }
-}
-
 impl<T: Debug> Debug for Cached<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Cached {{ {:?} }}", *self)
+        write!(f, "Cached {{ {:?} }}", self.0)
     }
 }
 
+/// Orders windows to create, given as `(entity, parent, order)`.
+///
+/// Parents come before the windows embedded in them, so children don't need to wait for another
+/// update, then the windows follow their [`CreationOrder`], then their entity index.
+fn creation_order(windows: Vec<(Entity, Option<Entity>, Option<CreationOrder>)>) -> Vec<Entity> {
+    let parents: EntityHashMap<Option<Entity>> = windows
+        .iter()
+        .map(|&(entity, parent, _)| (entity, parent))
+        .collect();
+    let depth = |mut entity: Entity| {
+        let mut depth = 0;
+        // Parents outside the batch already exist, so they don't delay their children.
+        while let Some(&Some(parent)) = parents.get(&entity) {
+            if !parents.contains_key(&parent) {
+                break;
+            }
+            // Parent cycles are bounded by the number of windows.
+            if depth >= parents.len() {
+                break;
+            }
+            depth += 1;
+            entity = parent;
+        }
+        depth
+    };
+    let mut order: Vec<_> = windows
+        .into_iter()
+        .map(|(entity, _, order)| {
+            let order = order.map_or(u64::MAX, |order| order.0 as u64);
+            (depth(entity), order, entity.index(), entity)
+        })
+        .collect();
+    order.sort_unstable();
+    order.into_iter().map(|(_, _, _, entity)| entity).collect()
+}
+
 /// Creates new windows on the [`winit`] backend for each entity with a newly-added
 /// [`Window`] component.
 ///
+/// Windows whose [`WindowHook::ready`] returns `false`, or whose [`WindowExtras::parent`] doesn't
+/// have a `winit` window yet, are skipped, and should be picked up again by a later call.
+///
+/// Windows are created in [`CreationOrder`], parents before the windows embedded in them.
+///
 /// If any of these entities are missing required components, those will be added with their
-/// default values.
+/// default values, unless [`WinitSettings::strict_window_components`] is enabled.
+///
+/// Each created window gets a [`WindowCreated`] and a [`WindowResized`] event, the latter with
+/// the size the window was actually created with.
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn create_windows<T: WindowHook, F: QueryFilter + 'static>(
     event_loop: &EventLoopWindowTarget<()>,
@@ -65,75 +136,549 @@ pub(crate) fn create_windows<T: WindowHook, F: QueryFilter + 'static>(
         mut commands,
         mut created_windows,
         mut window_created_events,
+        mut window_resized_events,
+        mut backend_created_events,
+        mut creation_failed_events,
         mut winit_windows,
         mut adapters,
         mut handlers,
         accessibility_requested,
+        settings,
     ): SystemParamItem<CreateWindowParams<T, F>>,
 ) {
-    for (entity, mut window, hook) in &mut created_windows {
+    let order = creation_order(
+        created_windows
+            .iter()
+            .map(|(entity, _, _, extras, _, order, _)| {
+                (
+                    entity,
+                    extras.and_then(|extras| extras.parent),
+                    order.copied(),
+                )
+            })
+            .collect(),
+    );
+
+    for entity in order {
+        let Ok((entity, mut window, hook, extras, has_raw_handle, _, quiet)) =
+            created_windows.get_mut(entity)
+        else {
+            continue;
+        };
         if winit_windows.get_window(entity).is_some() {
             continue;
         }
+        // The window id is only known once the window is created.
+        let span = info_span!("create_window", ?entity, window_id = field::Empty);
+        let _enter = span.enter();
+
+        let attempts = match winit_windows.creation_attempts.get(&entity) {
+            Some(&(attempts, last_failure)) => {
+                if attempts > settings.window_creation_retries
+                    || last_failure.elapsed()
+                        < creation_retry_delay(settings.window_creation_retry_delay, attempts)
+                {
+                    continue;
+                }
+                attempts
+            }
+            None => 0,
+        };
+
+        let parent = extras
+            .and_then(|extras| extras.parent)
+            .filter(|&parent| winit_windows.get_window(parent).is_none());
+        if let Some(parent) = parent.filter(|&parent| commands.get_entity(parent).is_none()) {
+            // The parent will never have a window, so stop waiting for it.
+            winit_windows.deferred.remove(&entity);
+            winit_windows.creation_attempts.insert(
+                entity,
+                (
+                    settings.window_creation_retries.saturating_add(1),
+                    Instant::now(),
+                ),
+            );
+            error!(
+                "Not creating window {:?} ({:?}): its parent {:?} doesn't exist",
+                window.title.as_str(),
+                entity,
+                parent
+            );
+            creation_failed_events.send(WindowCreationFailed {
+                window: entity,
+                error: format!("the parent window {parent:?} doesn't exist"),
+            });
+            continue;
+        }
 
-        info!(
+        let deferral = if hook.is_some_and(|hook| !hook.ready()) {
+            Some(Deferral::HookNotReady)
+        } else if let Some(parent) = parent {
+            Some(Deferral::Parent(parent))
+        } else if settings.strict_window_components && extras.is_none() {
+            Some(Deferral::MissingExtras)
+        } else {
+            None
+        };
+        if let Some(deferral) = deferral {
+            // Each reason is logged once, when the window starts waiting for it.
+            if winit_windows.deferred.insert(entity, deferral) != Some(deferral) {
+                match deferral {
+                    Deferral::HookNotReady => info_unless_quiet!(
+                        quiet,
+                        "Deferring creation of window {:?} ({:?}) until its hook is ready",
+                        window.title.as_str(),
+                        entity
+                    ),
+                    Deferral::Parent(parent) => warn!(
+                        "Deferring creation of window {:?} ({:?}) until its parent {:?} is created",
+                        window.title.as_str(),
+                        entity,
+                        parent
+                    ),
+                    Deferral::MissingExtras => error!(
+                        "Not creating window {:?} ({:?}): it has no WindowExtras component",
+                        window.title.as_str(),
+                        entity
+                    ),
+                }
+            }
+            continue;
+        }
+        winit_windows.deferred.remove(&entity);
+
+        info_unless_quiet!(
+            quiet,
             "Creating new window {:?} ({:?})",
             window.title.as_str(),
             entity
         );
 
-        let winit_window = winit_windows.create_window(
+        if quiet {
+            winit_windows.quiet.insert(entity);
+        }
+        let winit_window = match winit_windows.try_create_window(
             event_loop,
             entity,
-            &window,
+            &mut window,
             hook,
+            extras,
             &mut adapters,
             &mut handlers,
             &accessibility_requested,
-        );
-
-        if let Some(theme) = winit_window.theme() {
-            window.window_theme = Some(convert_winit_theme(theme));
-        }
+        ) {
+            Ok(winit_window) => winit_window,
+            Err(err) => {
+                let attempts = attempts + 1;
+                winit_windows.quiet.remove(&entity);
+                winit_windows
+                    .creation_attempts
+                    .insert(entity, (attempts, Instant::now()));
+                if attempts > settings.window_creation_retries {
+                    error!(
+                        "Failed to create window {:?} ({:?}), giving up: {}",
+                        window.title.as_str(),
+                        entity,
+                        err
+                    );
+                    creation_failed_events.send(WindowCreationFailed {
+                        window: entity,
+                        error: err.to_string(),
+                    });
+                } else {
+                    warn!(
+                        "Failed to create window {:?} ({:?}), retrying ({}/{}): {}",
+                        window.title.as_str(),
+                        entity,
+                        attempts,
+                        settings.window_creation_retries,
+                        err
+                    );
+                }
+                continue;
+            }
+        };
+        span.record("window_id", field::debug(winit_window.id()));
 
-        window
-            .resolution
-            .set_scale_factor(winit_window.scale_factor() as f32);
-        commands
-            .entity(entity)
-            .insert(RawHandleWrapper {
+        let (resized, backend_created) = initial_size_events(
+            entity,
+            &mut window,
+            winit_window.id(),
+            winit_window.inner_size(),
+            winit_window.scale_factor(),
+        );
+        let mut entity_commands = commands.entity(entity);
+        insert_raw_handle(
+            &mut entity_commands,
+            RawHandleWrapper {
                 window_handle: winit_window.window_handle().unwrap().as_raw(),
                 display_handle: winit_window.display_handle().unwrap().as_raw(),
-            })
-            .insert(CachedWindow {
-                window: window.clone(),
-            });
+            },
+            has_raw_handle,
+        );
+        entity_commands
+            .insert(Cached(window.clone()))
+            .insert(Cached(window.cursor))
+            .insert(WinitWindowState::from_winit(winit_window))
+            .insert(EffectiveWindowTheme(
+                winit_window.theme().map(convert_winit_theme),
+            ))
+            .insert(
+                winit_window
+                    .current_monitor()
+                    .map(|monitor| CurrentMonitor::from_winit(&monitor))
+                    .unwrap_or_default(),
+            )
+            .insert(ExclusiveVideoMode(exclusive_videomode(winit_window)));
+        if let Some(hook) = hook {
+            entity_commands.insert(Cached(hook.clone()));
+        }
+        let extras = extras.cloned().unwrap_or_else(|| {
+            let extras = WindowExtras::default();
+            entity_commands.insert(extras.clone());
+            extras
+        });
+        entity_commands.insert(Cached(extras));
 
         window_created_events.send(WindowCreated { window: entity });
+        window_resized_events.send(resized);
+        backend_created_events.send(backend_created);
+    }
+}
+
+/// Inserts the `handle` of a newly created window, replacing the one the entity already had if
+/// `has_raw_handle` is set.
+fn insert_raw_handle(
+    entity_commands: &mut EntityCommands,
+    handle: RawHandleWrapper,
+    has_raw_handle: bool,
+) {
+    if has_raw_handle {
+        // Left over from a previous window of this entity, e.g. after a backend reset.
+        // Remove it first so the renderer sees a new handle rather than a modified one.
+        warn!(
+            "Window {:?} already had a raw window handle, replacing it",
+            entity_commands.id()
+        );
+        entity_commands.remove::<RawHandleWrapper>();
     }
+    entity_commands.insert(handle);
 }
 
-pub(crate) fn despawn_windows(
+/// Sizes a newly created `window` after the `inner_size` and `scale_factor` its `winit` window was
+/// actually created with, returning the events announcing them.
+///
+/// The platform may not have honored the requested size, and may never send a resize.
+fn initial_size_events(
+    entity: Entity,
+    window: &mut Window,
+    window_id: winit::window::WindowId,
+    inner_size: PhysicalSize<u32>,
+    scale_factor: f64,
+) -> (WindowResized, WindowBackendCreated) {
+    let scale_factor = sanitize_scale_factor(scale_factor);
+    window.resolution.set_scale_factor(scale_factor as f32);
+    window
+        .resolution
+        .set_physical_resolution(inner_size.width, inner_size.height);
+    let resized = WindowResized {
+        window: entity,
+        width: window.width(),
+        height: window.height(),
+    };
+    let backend_created = WindowBackendCreated {
+        window: entity,
+        window_id,
+        scale_factor,
+    };
+    (resized, backend_created)
+}
+
+/// The caret height in logical pixels used for [`Window::ime_position`], which has no height.
+const DEFAULT_IME_CARET_HEIGHT: f32 = 10.0;
+
+/// How many updates a [`WindowHook`] can postpone the destruction of its window for.
+const MAX_DESTROY_POSTPONEMENTS: u32 = 120;
+
+/// Destroys the [`winit`] windows of entities whose [`Window`] component was removed.
+///
+/// Destruction is postponed for as long as [`WindowHook::before_destroy`] returns `false`, up to
+/// [`MAX_DESTROY_POSTPONEMENTS`] updates. [`WindowClosed`] is only sent once the window is
+/// actually destroyed.
+pub(crate) fn despawn_windows<T: WindowHook>(
+    mut commands: Commands,
     mut closed: RemovedComponents<Window>,
     window_entities: Query<&Window>,
+    mut hooks: Query<&mut T>,
+    mut postponed: Local<EntityHashMap<u32>>,
     mut close_events: EventWriter<WindowClosed>,
     mut winit_windows: NonSendMut<WinitWindows>,
 ) {
     for window in closed.read() {
-        info!("Closing window {:?}", window);
+        winit_windows.deferred.remove(&window);
+        winit_windows.creation_attempts.remove(&window);
+        info_unless_quiet!(
+            winit_windows.quiet.contains(&window),
+            "Closing window {:?}",
+            window
+        );
+        postponed.entry(window).or_insert(0);
+    }
+
+    postponed.retain(|&window, postponements| {
+        let _span = info_span!(
+            "despawn_window",
+            entity = ?window,
+            window_id = ?winit_windows.entity_to_winit.get(&window),
+        )
+        .entered();
+
         // Guard to verify that the window is in fact actually gone,
         // rather than having the component added and removed in the same frame.
-        if !window_entities.contains(window) {
-            winit_windows.remove_window(window);
-            close_events.send(WindowClosed { window });
+        if window_entities.contains(window) {
+            return false;
+        }
+
+        if let Ok(mut hook) = hooks.get_mut(window) {
+            if *postponements >= MAX_DESTROY_POSTPONEMENTS {
+                warn!(
+                    "Window {:?} was postponed for {} updates, destroying it anyway",
+                    window, postponements
+                );
+            } else if !hook.before_destroy(window) {
+                *postponements += 1;
+                return true;
+            }
+        }
+
+        winit_windows.remove_window(window);
+        // Without its caches the entity is picked up by `create_windows` again if a `Window` is
+        // re-added, so it always ends up with exactly one winit window.
+        if let Some(mut entity_commands) = commands.get_entity(window) {
+            entity_commands.remove::<(
+                RawHandleWrapper,
+                Cached<Window>,
+                Cached<Cursor>,
+                Cached<WindowExtras>,
+                Cached<T>,
+            )>();
+        }
+        close_events.send(WindowClosed { window });
+        false
+    });
+}
+
+/// Applies [`Window::enabled_buttons`], syncing back what the platform actually applied.
+fn apply_enabled_buttons(winit_window: &winit::window::Window, window: &mut Window) {
+    let enabled_buttons = convert_enabled_buttons(window.enabled_buttons);
+    if winit_window.enabled_buttons() == enabled_buttons {
+        return;
+    }
+    winit_window.set_enabled_buttons(enabled_buttons);
+
+    // Not every platform supports all combinations, so sync back what was applied.
+    let applied = convert_winit_enabled_buttons(winit_window.enabled_buttons());
+    if applied != window.enabled_buttons {
+        warn!(
+            "Could not set enabled buttons {:?} for window {:?}, the platform applied {:?}",
+            window.enabled_buttons, window.title, applied
+        );
+        window.enabled_buttons = applied;
+    }
+}
+
+/// The geometry [`changed_windows`] applies to a window.
+///
+/// Geometry changes are applied back to back in the order of these fields, since winit has no
+/// combined move + resize: constraints first so the new size isn't clamped by stale limits, then
+/// the position (which is computed from the new size), then the size itself.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct GeometryUpdates {
+    /// Pins the size of a non-resizable window, which also applies its constraints.
+    pin_size: bool,
+    constraints: bool,
+    position: bool,
+    size: bool,
+    /// Holds the changes back until the window is restored.
+    hold_back: bool,
+}
+
+impl GeometryUpdates {
+    /// Compares `window` against what was last applied.
+    ///
+    /// Geometry changes are meaningless for `minimized` windows and error on some platforms, so
+    /// they are held back, and everything is re-applied once the window is restored with
+    /// `pending` changes.
+    fn new(window: &Window, cache: &Window, force: bool, minimized: bool, pending: bool) -> Self {
+        if minimized {
+            let geometry_changed = window.resize_constraints != cache.resize_constraints
+                || window.position != cache.position
+                || window.resolution != cache.resolution;
+            return Self {
+                hold_back: force || geometry_changed,
+                ..Default::default()
+            };
+        }
+        let force = force || pending;
+        let resizable_changed = window.resizable != cache.resizable;
+        let constraints_changed = window.resize_constraints != cache.resize_constraints;
+        let position = force || window.position != cache.position;
+        let size = force || window.resolution != cache.resolution;
+        let pin_size = !window.resizable
+            && (force || resizable_changed || constraints_changed || position || size);
+        Self {
+            pin_size,
+            constraints: !pin_size && (force || resizable_changed || constraints_changed),
+            position,
+            size,
+            hold_back: false,
+        }
+    }
+}
+
+/// The outer size a window with the given `outer` and `inner` sizes will have once its inner size
+/// is set to `resolution`, keeping the size of its decorations.
+fn outer_size_after_resize(
+    outer: PhysicalSize<u32>,
+    inner: PhysicalSize<u32>,
+    resolution: &WindowResolution,
+) -> PhysicalSize<u32> {
+    PhysicalSize::new(
+        outer.width.saturating_sub(inner.width) + resolution.physical_width(),
+        outer.height.saturating_sub(inner.height) + resolution.physical_height(),
+    )
+}
+
+/// Remembers the `inner_size` of a window entering fullscreen in `windowed_sizes`, returning the
+/// size to restore if it's leaving fullscreen instead.
+///
+/// Some platforms restore windows leaving fullscreen to a tiny size. Switching between fullscreen
+/// modes keeps the size the window had before entering the first one.
+fn track_windowed_size(
+    windowed_sizes: &mut EntityHashMap<PhysicalSize<u32>>,
+    entity: Entity,
+    was_fullscreen: bool,
+    fullscreen: bool,
+    inner_size: PhysicalSize<u32>,
+) -> Option<PhysicalSize<u32>> {
+    match (was_fullscreen, fullscreen) {
+        (false, true) => {
+            windowed_sizes.insert(entity, inner_size);
+            None
+        }
+        (true, false) => windowed_sizes.remove(&entity),
+        _ => None,
+    }
+}
+
+/// When [`changed_windows`] applies [`Window::enabled_buttons`], relative to
+/// [`Window::decorations`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ButtonsUpdate {
+    Unchanged,
+    BeforeDecorations,
+    AfterDecorations,
+}
+
+impl ButtonsUpdate {
+    /// Both restyle the window on some platforms (e.g. Windows). Buttons are changed while the
+    /// window is undecorated, so a title bar only appears or disappears once, with its final
+    /// buttons.
+    fn new(buttons_changed: bool, decorations_changed: bool, decorations: bool) -> Self {
+        match (buttons_changed, decorations_changed && decorations) {
+            (false, _) => Self::Unchanged,
+            (true, true) => Self::BeforeDecorations,
+            (true, false) => Self::AfterDecorations,
         }
     }
 }
 
-/// The cached state of the window so we can check which properties were changed from within the app.
-#[derive(Debug, Clone, Component)]
-pub struct CachedWindow {
-    pub window: Window,
+/// Whether [`changed_windows`] needs to apply [`Window::decorations`], given whether the winit
+/// window is currently `decorated`.
+///
+/// Hit-test passthrough depends on the window style, so a previously applied hit test may no
+/// longer hold once this is applied, and is re-applied along with it.
+fn decorations_need_apply(window: &Window, cache: &Window, force: bool, decorated: bool) -> bool {
+    let returned_to_windowed =
+        window.mode == WindowMode::Windowed && cache.mode != WindowMode::Windowed;
+    (force || returned_to_windowed || window.decorations != cache.decorations)
+        && window.mode == WindowMode::Windowed
+        && window.decorations != decorated
+}
+
+/// Whether `window` differs from `cache` in anything [`changed_windows`] applies, i.e. in anything
+/// but its cursor, which [`changed_cursors`] applies.
+fn window_changed_beyond_cursor(window: &Window, cache: &Window) -> bool {
+    // Peek at the requests on a copy, since taking them is what applies them.
+    let mut internal = window.internal;
+    internal.take_maximize_request().is_some()
+        || internal.take_minimize_request().is_some()
+        || window.title != cache.title
+        || window.mode != cache.mode
+        || window.position != cache.position
+        || window.resolution != cache.resolution
+        || window.resize_constraints != cache.resize_constraints
+        || window.resizable != cache.resizable
+        || window.enabled_buttons != cache.enabled_buttons
+        || window.decorations != cache.decorations
+        || window.transparent != cache.transparent
+        || window.focused != cache.focused
+        || window.window_level != cache.window_level
+        || window.canvas != cache.canvas
+        || window.ime_enabled != cache.ime_enabled
+        || window.ime_position != cache.ime_position
+        || window.window_theme != cache.window_theme
+        || window.visible != cache.visible
+}
+
+/// Whether two windows are the same, since [`Window`] isn't `PartialEq`.
+fn windows_eq(a: &Window, b: &Window) -> bool {
+    !cursor_changed(&a.cursor, &b.cursor)
+        && a.present_mode == b.present_mode
+        && a.mode == b.mode
+        && a.position == b.position
+        && a.resolution == b.resolution
+        && a.title == b.title
+        && a.name == b.name
+        && a.composite_alpha_mode == b.composite_alpha_mode
+        && a.resize_constraints == b.resize_constraints
+        && a.resizable == b.resizable
+        && a.enabled_buttons == b.enabled_buttons
+        && a.decorations == b.decorations
+        && a.transparent == b.transparent
+        && a.focused == b.focused
+        && a.window_level == b.window_level
+        && a.canvas == b.canvas
+        && a.prevent_default_event_handling == b.prevent_default_event_handling
+        && a.internal == b.internal
+        && a.ime_enabled == b.ime_enabled
+        && a.ime_position == b.ime_position
+        && a.window_theme == b.window_theme
+        && a.visible == b.visible
+}
+
+/// Writes `window` to its `cache` like [`Mut::set_if_neq`], so the cache is only marked changed
+/// if anything differs.
+fn set_window_cache_if_neq(cache: &mut Mut<Cached<Window>>, window: &Window) {
+    if !windows_eq(&cache.0, window) {
+        cache.0 = window.clone();
+    }
+}
+
+/// Writes `cursor` to its `cache` like [`Mut::set_if_neq`], so the cache is only marked changed
+/// if anything differs.
+fn set_cursor_cache_if_neq(cache: &mut Mut<Cached<Cursor>>, cursor: Cursor) {
+    if cursor_changed(&cursor, &cache.0) {
+        cache.0 = cursor;
+    }
+}
+
+/// Whether `cursor` differs from `cache` in anything [`changed_cursors`] applies.
+fn cursor_changed(cursor: &Cursor, cache: &Cursor) -> bool {
+    cursor.icon != cache.icon
+        || cursor.visible != cache.visible
+        || cursor.grab_mode != cache.grab_mode
+        || cursor.hit_test != cache.hit_test
 }
 
 /// Propagates changes from [`Window`] entities to the [`winit`] backend.
@@ -144,21 +689,66 @@ pub struct CachedWindow {
 /// - [`Window::transparent`] cannot be changed after the window is created.
 /// - [`Window::canvas`] cannot be changed after the window is created.
 /// - [`Window::focused`] cannot be manually changed to `false` after the window is created.
-pub(crate) fn changed_windows(
-    mut changed_windows: Query<(Entity, &mut Window, &mut Cached<Window>), Changed<Window>>,
-    winit_windows: NonSendMut<WinitWindows>,
+/// - The cursor position of the [`Window`] is only observed, use [`CursorWarp`] to move the cursor.
+/// - Windows invalidated with [`WinitWindows::invalidate_cache`] have all their properties
+///   re-applied, except for focus.
+#[allow(clippy::type_complexity)]
+pub(crate) fn changed_windows<F: QueryFilter>(
+    mut changed_windows: Query<
+        (
+            Entity,
+            &mut Window,
+            &mut Cached<Window>,
+            Option<&mut ExclusiveVideoMode>,
+            Option<&mut EffectiveWindowTheme>,
+            Option<&WindowExtras>,
+        ),
+        F,
+    >,
+    mut winit_windows: NonSendMut<WinitWindows>,
     mut window_resized: EventWriter<WindowResized>,
+    settings: Res<WinitSettings>,
 ) {
-    for (entity, mut window, mut cache) in &mut changed_windows {
+    // Taken for the duration of the loop, since the windows are borrowed from `winit_windows`.
+    let mut windowed_sizes = std::mem::take(&mut winit_windows.windowed_sizes);
+    let mut requested_video_modes = std::mem::take(&mut winit_windows.requested_video_modes);
+    let mut soft_keyboard_requests = Vec::new();
+    let mut fullscreen_changes = Vec::new();
+    for (entity, mut window, mut cache, videomode, effective_theme, extras) in &mut changed_windows
+    {
+        // Invalidated windows have every property re-applied, even if nothing changed.
+        let force = winit_windows.invalidated.contains(&entity);
+        let pending_geometry = winit_windows.pending_geometry.contains(&entity);
+        // Cursor-only changes are left to `changed_cursors`, which also keeps the cursor of this
+        // cache in sync.
+        let window_changed = window.is_changed() && window_changed_beyond_cursor(&window, &cache);
+        if !force && !pending_geometry && !window_changed {
+            continue;
+        }
         let Some(winit_window) = winit_windows.get_window(entity) else {
             continue;
         };
 
-        if window.title != cache.title {
+        // Geometry changes are meaningless for minimized windows and error on some platforms, so
+        // they are held back and re-applied once the window is restored. Asking the platform is a
+        // round trip, so it's only done once there is something to apply.
+        let minimized = winit_window.is_minimized() == Some(true);
+        let restored = !minimized && pending_geometry;
+        if !force && !restored && !window_changed {
+            continue;
+        }
+        let _span = info_span!("changed_window", ?entity, window_id = ?winit_window.id()).entered();
+        let geometry = GeometryUpdates::new(&window, &cache, force, minimized, pending_geometry);
+        let physical_constraints = extras.is_some_and(|extras| extras.physical_resize_constraints);
+        if window.resize_constraints != cache.resize_constraints {
+            warn_invalid_constraints(&window.resize_constraints);
+        }
+
+        if force || window.title != cache.title {
             winit_window.set_title(window.title.as_str());
         }
 
-        if window.mode != cache.mode {
+        if force || window.mode != cache.mode {
             let new_mode = match window.mode {
                 WindowMode::BorderlessFullscreen => {
                     Some(Some(winit::window::Fullscreen::Borderless(None)))
@@ -166,7 +756,10 @@ pub(crate) fn changed_windows(
                 mode @ (WindowMode::Fullscreen | WindowMode::SizedFullscreen) => {
                     if let Some(current_monitor) = winit_window.current_monitor() {
                         let videomode = match mode {
-                            WindowMode::Fullscreen => get_best_videomode(&current_monitor),
+                            WindowMode::Fullscreen => requested_video_modes
+                                .get(&entity)
+                                .and_then(|&mode| find_videomode(&current_monitor, mode))
+                                .or_else(|| get_best_videomode(&current_monitor)),
                             WindowMode::SizedFullscreen => get_fitting_videomode(
                                 &current_monitor,
                                 window.width() as u32,
@@ -175,7 +768,15 @@ pub(crate) fn changed_windows(
                             _ => unreachable!(),
                         };
 
-                        Some(Some(winit::window::Fullscreen::Exclusive(videomode)))
+                        window.mode = exclusive_fullscreen_fallback(mode, videomode.is_some());
+                        if let Some(videomode) = videomode {
+                            Some(Some(winit::window::Fullscreen::Exclusive(videomode)))
+                        } else {
+                            warn!("Current monitor reports no video modes, falling back to borderless fullscreen for window {:?}", window.title);
+                            Some(Some(winit::window::Fullscreen::Borderless(Some(
+                                current_monitor,
+                            ))))
+                        }
                     } else {
                         warn!("Could not determine current monitor, ignoring exclusive fullscreen request for window {:?}", window.title);
                         None
@@ -183,104 +784,183 @@ pub(crate) fn changed_windows(
                 }
                 WindowMode::Windowed => Some(None),
             };
+            if window.mode != WindowMode::Fullscreen {
+                requested_video_modes.remove(&entity);
+            }
 
             if let Some(new_mode) = new_mode {
                 if winit_window.fullscreen() != new_mode {
+                    let restored_size = track_windowed_size(
+                        &mut windowed_sizes,
+                        entity,
+                        winit_window.fullscreen().is_some(),
+                        new_mode.is_some(),
+                        winit_window.inner_size(),
+                    );
                     winit_window.set_fullscreen(new_mode);
+                    fullscreen_changes.push((entity, window.mode));
+
+                    if let Some(size) = restored_size {
+                        if let Some(size_now) = winit_window.request_inner_size(size) {
+                            crate::react_to_resize(
+                                &mut window,
+                                size_now,
+                                &mut window_resized,
+                                entity,
+                            );
+                        }
+                    }
                 }
             }
+
+            if let Some(mut videomode) = videomode {
+                videomode.set_if_neq(ExclusiveVideoMode(exclusive_videomode(winit_window)));
+            }
         }
 
-        if window.resolution != cache.resolution {
+        if geometry.pin_size {
+            // Pin the size of non-resizable windows, since some platforms still let the user
+            // resize them within their constraints, or show a maximize button for them.
+            let scale_factor = match physical_constraints {
+                true => 1.0,
+                false => winit_window.scale_factor(),
+            };
             let physical_size = PhysicalSize::new(
                 window.resolution.physical_width(),
                 window.resolution.physical_height(),
             );
-            if let Some(size_now) = winit_window.request_inner_size(physical_size) {
-                crate::react_to_resize(&mut window, size_now, &mut window_resized, entity);
-            }
+            pin_inner_size(
+                winit_window,
+                clamp_to_constraints(physical_size, &window.resize_constraints, scale_factor),
+            );
+        } else if geometry.constraints {
+            apply_resize_constraints(
+                winit_window,
+                &window.resize_constraints,
+                physical_constraints,
+            );
         }
 
-        if window.physical_cursor_position() != cache.physical_cursor_position() {
-            if let Some(physical_position) = window.physical_cursor_position() {
-                let position = PhysicalPosition::new(physical_position.x, physical_position.y);
+        if geometry.position {
+            let position = crate::winit_window_position(
+                &window.position,
+                &window.resolution,
+                winit_window.available_monitors(),
+                winit_window.primary_monitor(),
+                winit_window.current_monitor(),
+            )
+            .or_else(|| {
+                fallback_window_position(
+                    settings.position_fallback,
+                    &window.resolution,
+                    winit_window.primary_monitor(),
+                )
+            });
+            if let Some(position) = position {
+                let position = settings
+                    .min_visible_window_size
+                    .and_then(|min_visible| {
+                        // The size is applied after the position, so clamp with the size the
+                        // window is about to have rather than its current one.
+                        let outer_size = outer_size_after_resize(
+                            winit_window.outer_size(),
+                            winit_window.inner_size(),
+                            &window.resolution,
+                        );
+                        clamp_to_monitors(
+                            position,
+                            outer_size,
+                            winit_window.available_monitors(),
+                            min_visible,
+                        )
+                    })
+                    .map_or(position, |clamped| {
+                        warn!(
+                            "Window {:?} would be moved off-screen to {:?}, clamping to {:?}",
+                            window.title, position, clamped
+                        );
+                        clamped
+                    });
+
+                let should_set = match winit_window.outer_position() {
+                    Ok(current_position) => current_position != position,
+                    _ => true,
+                };
 
-                if let Err(err) = winit_window.set_cursor_position(position) {
-                    error!("could not set cursor position: {:?}", err);
+                if should_set {
+                    winit_window.set_outer_position(position);
                 }
             }
         }
 
-        if window.cursor.icon != cache.cursor.icon {
-            winit_window.set_cursor_icon(converters::convert_cursor_icon(window.cursor.icon));
+        if geometry.size {
+            let physical_size = PhysicalSize::new(
+                window.resolution.physical_width(),
+                window.resolution.physical_height(),
+            );
+            // Request a size within the app's constraints, since the OS may not know about all of
+            // them yet, and correct it once more if the OS still picked a size outside of them.
+            let scale_factor = match physical_constraints {
+                true => 1.0,
+                false => winit_window.scale_factor(),
+            };
+            let constraints = window.resize_constraints;
+            let physical_size = match winit_windows.aspect_ratio(entity) {
+                Some(ratio) => {
+                    let previous = PhysicalSize::new(
+                        cache.resolution.physical_width(),
+                        cache.resolution.physical_height(),
+                    );
+                    fit_aspect_ratio(physical_size, previous, ratio)
+                }
+                None => physical_size,
+            };
+            let physical_size = clamp_to_constraints(physical_size, &constraints, scale_factor);
+            if let Some(size_now) = winit_window.request_inner_size(physical_size) {
+                let clamped = clamp_to_constraints(size_now, &constraints, scale_factor);
+                let size_now = if clamped == size_now {
+                    size_now
+                } else {
+                    winit_window.request_inner_size(clamped).unwrap_or(clamped)
+                };
+                crate::react_to_resize(&mut window, size_now, &mut window_resized, entity);
+            }
         }
 
-        if window.cursor.grab_mode != cache.cursor.grab_mode {
-            crate::winit_windows::attempt_grab(winit_window, window.cursor.grab_mode);
-        }
+        // Decorations interact with fullscreen, so they are only applied while windowed. The
+        // desired state is kept in `window.decorations` (and thus the cache) and re-applied when
+        // the window returns to `Windowed`.
+        let decorations_changed =
+            decorations_need_apply(&window, &cache, force, winit_window.is_decorated());
+        let buttons = ButtonsUpdate::new(
+            force || window.enabled_buttons != cache.enabled_buttons,
+            decorations_changed,
+            window.decorations,
+        );
 
-        if window.cursor.visible != cache.cursor.visible {
-            winit_window.set_cursor_visible(window.cursor.visible);
+        if buttons == ButtonsUpdate::BeforeDecorations {
+            apply_enabled_buttons(winit_window, &mut window);
         }
 
-        if window.cursor.hit_test != cache.cursor.hit_test {
+        if decorations_changed {
+            winit_window.set_decorations(window.decorations);
+
             if let Err(err) = winit_window.set_cursor_hittest(window.cursor.hit_test) {
-                window.cursor.hit_test = cache.cursor.hit_test;
                 warn!(
-                    "Could not set cursor hit test for window {:?}: {:?}",
+                    "Could not re-apply cursor hit test for window {:?} after changing decorations: {:?}",
                     window.title, err
                 );
             }
         }
 
-        if window.decorations != cache.decorations
-            && window.decorations != winit_window.is_decorated()
+        if (force || window.resizable != cache.resizable)
+            && window.resizable != winit_window.is_resizable()
         {
-            winit_window.set_decorations(window.decorations);
-        }
-
-        if window.resizable != cache.resizable && window.resizable != winit_window.is_resizable() {
             winit_window.set_resizable(window.resizable);
         }
 
-        if window.enabled_buttons != cache.enabled_buttons {
-            winit_window.set_enabled_buttons(convert_enabled_buttons(window.enabled_buttons));
-        }
-
-        if window.resize_constraints != cache.resize_constraints {
-            let constraints = window.resize_constraints.check_constraints();
-            let min_inner_size = LogicalSize {
-                width: constraints.min_width,
-                height: constraints.min_height,
-            };
-            let max_inner_size = LogicalSize {
-                width: constraints.max_width,
-                height: constraints.max_height,
-            };
-
-            winit_window.set_min_inner_size(Some(min_inner_size));
-            if constraints.max_width.is_finite() && constraints.max_height.is_finite() {
-                winit_window.set_max_inner_size(Some(max_inner_size));
-            }
-        }
-
-        if window.position != cache.position {
-            if let Some(position) = crate::winit_window_position(
-                &window.position,
-                &window.resolution,
-                winit_window.available_monitors(),
-                winit_window.primary_monitor(),
-                winit_window.current_monitor(),
-            ) {
-                let should_set = match winit_window.outer_position() {
-                    Ok(current_position) => current_position != position,
-                    _ => true,
-                };
-
-                if should_set {
-                    winit_window.set_outer_position(position);
-                }
-            }
+        if buttons == ButtonsUpdate::AfterDecorations {
+            apply_enabled_buttons(winit_window, &mut window);
         }
 
         if let Some(maximized) = window.internal.take_maximize_request() {
@@ -295,16 +975,21 @@ pub(crate) fn changed_windows(
             winit_window.focus_window();
         }
 
-        if window.window_level != cache.window_level {
+        if force || window.window_level != cache.window_level {
             winit_window.set_window_level(convert_window_level(window.window_level));
         }
 
         // Currently unsupported changes
-        if window.transparent != cache.transparent {
+        let transparency_reverted = window.transparent != cache.transparent;
+        if transparency_reverted {
             window.transparent = cache.transparent;
             warn!("Winit does not currently support updating transparency after window creation.");
         }
 
+        if window.decorations != cache.decorations || transparency_reverted {
+            check_hit_test_passthrough(&window);
+        }
+
         #[cfg(target_arch = "wasm32")]
         if window.canvas != cache.canvas {
             window.canvas = cache.canvas.clone();
@@ -313,37 +998,1085 @@ pub(crate) fn changed_windows(
             );
         }
 
-        if window.ime_enabled != cache.ime_enabled {
+        if force || window.ime_enabled != cache.ime_enabled {
             winit_window.set_ime_allowed(window.ime_enabled);
+            soft_keyboard_requests.push((entity, window.ime_enabled));
         }
 
-        if window.ime_position != cache.ime_position {
-            winit_window.set_ime_cursor_area(
-                LogicalPosition::new(window.ime_position.x, window.ime_position.y),
-                PhysicalSize::new(10, 10),
+        if force || window.ime_position != cache.ime_position {
+            let (position, size) = converters::ime_area(
+                window.ime_position,
+                DEFAULT_IME_CARET_HEIGHT,
+                winit_window.scale_factor(),
             );
+            winit_window.set_ime_cursor_area(position, size);
         }
 
-        if window.window_theme != cache.window_theme {
+        if force || window.window_theme != cache.window_theme {
             winit_window.set_theme(window.window_theme.map(convert_window_theme));
+            if let Some(mut effective_theme) = effective_theme {
+                effective_theme.set_if_neq(EffectiveWindowTheme(
+                    winit_window.theme().map(convert_winit_theme),
+                ));
+            }
         }
 
-        if window.visible != cache.visible {
+        if force || window.visible != cache.visible {
             winit_window.set_visible(window.visible);
         }
 
-        **cache = window.clone();
+        set_window_cache_if_neq(&mut cache, &window);
+
+        if geometry.hold_back {
+            winit_windows.pending_geometry.insert(entity);
+        } else if !minimized {
+            winit_windows.pending_geometry.remove(&entity);
+        }
     }
+
+    winit_windows.windowed_sizes = windowed_sizes;
+    winit_windows.requested_video_modes = requested_video_modes;
+    winit_windows
+        .soft_keyboard_requests
+        .extend(soft_keyboard_requests);
+    winit_windows.fullscreen_changes.extend(fullscreen_changes);
 }
 
-pub(crate) fn changed_hooks<T: WindowHook>(
-    mut changed_hooks: Query<(Entity, &mut T, &mut Cached<T>), Changed<T>>,
-    winit_windows: NonSendMut<WinitWindows>,
+/// Syncs [`Window::mode`] of windows switched with [`WinitWindows::set_exclusive_video_mode`].
+pub(crate) fn applied_video_modes(
+    mut windows: Query<(
+        &mut Window,
+        &mut Cached<Window>,
+        Option<&mut ExclusiveVideoMode>,
+    )>,
+    mut winit_windows: NonSendMut<WinitWindows>,
 ) {
-    for (entity, mut data, mut cache) in &mut changed_hooks {
-        if let Some(winit_window) = winit_windows.get_window(entity) {
-            data.changed_hook(winit_window, &cache);
-            **cache = data.clone();
+    let changes = std::mem::take(&mut winit_windows.video_mode_changes);
+    for entity in changes {
+        let (Ok((mut window, mut cache, videomode)), Some(winit_window)) =
+            (windows.get_mut(entity), winit_windows.get_window(entity))
+        else {
+            continue;
+        };
+        // The mode is already applied, so keep the cache in sync to not apply it again.
+        window.mode = WindowMode::Fullscreen;
+        cache.0.mode = WindowMode::Fullscreen;
+        if let Some(mut videomode) = videomode {
+            videomode.set_if_neq(ExclusiveVideoMode(exclusive_videomode(winit_window)));
+        }
+    }
+}
+
+/// The cursor properties [`changed_cursors`] applies to the [`winit`] window.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct CursorUpdates {
+    grab_mode: bool,
+    icon: bool,
+    visible: bool,
+    hit_test: bool,
+}
+
+impl CursorUpdates {
+    /// Compares `cursor` against what was last applied, applying everything if `force` is set.
+    fn new(cursor: &Cursor, cache: &Cursor, force: bool) -> Self {
+        let grab_mode = force || cursor.grab_mode != cache.grab_mode;
+        Self {
+            grab_mode,
+            // Some platforms reset the cursor icon when grabbing or releasing it, so reassert it.
+            icon: grab_mode || cursor.icon != cache.icon,
+            visible: force || cursor.visible != cache.visible,
+            hit_test: force || cursor.hit_test != cache.hit_test,
+        }
+    }
+}
+
+/// Propagates changes to [`Window::cursor`] to the [`winit`] backend.
+///
+/// Cursors change far more often than other window properties, so they are cached separately
+/// from [`changed_windows`], which skips cursor-only changes. The cursor of [`Cached<Window>`] is
+/// kept in sync, so neither system applies what the other already did.
+#[allow(clippy::type_complexity)]
+pub(crate) fn changed_cursors<F: QueryFilter>(
+    mut changed_windows: Query<
+        (
+            Entity,
+            &mut Window,
+            &mut Cached<Cursor>,
+            &mut Cached<Window>,
+        ),
+        F,
+    >,
+    mut winit_windows: NonSendMut<WinitWindows>,
+) {
+    let mut cursor_visibility = Vec::new();
+    let mut cursor_policies = Vec::new();
+    for (entity, mut window, mut cache, mut window_cache) in &mut changed_windows {
+        let force = winit_windows.invalidated.contains(&entity);
+        let changed = window.is_changed() && cursor_changed(&window.cursor, &cache);
+        if !force && !changed {
+            continue;
+        }
+        let Some(winit_window) = winit_windows.get_window(entity) else {
+            continue;
+        };
+
+        let updates = CursorUpdates::new(&window.cursor, &cache, force);
+        if updates.grab_mode {
+            window.cursor.grab_mode = attempt_grab(winit_window, window.cursor.grab_mode);
+        }
+
+        if updates.icon {
+            winit_window.set_cursor_icon(converters::convert_cursor_icon(window.cursor.icon));
+        }
+
+        if updates.visible {
+            winit_window.set_cursor_visible(window.cursor.visible);
+            cursor_visibility.push((entity, window.cursor.visible));
+        }
+
+        if updates.hit_test {
+            if let Err(err) = winit_window.set_cursor_hittest(window.cursor.hit_test) {
+                window.cursor.hit_test = cache.hit_test;
+                warn!(
+                    "Could not set cursor hit test for window {:?}: {:?}",
+                    window.title, err
+                );
+            }
+        }
+
+        if window.cursor.hit_test != cache.hit_test {
+            check_hit_test_passthrough(&window);
+        }
+
+        if force || cursor_changed(&window.cursor, &cache) {
+            cursor_policies.push(entity);
+        }
+
+        set_cursor_cache_if_neq(&mut cache, window.cursor);
+        if cursor_changed(&window.cursor, &window_cache.cursor) {
+            window_cache.0.cursor = window.cursor;
         }
     }
+
+    winit_windows.cursor_visibility.extend(cursor_visibility);
+    winit_windows.cursor_policy_requests.extend(cursor_policies);
+}
+
+/// Syncs [`Window::cursor`] of windows changed with [`WinitWindows::set_cursor_visible`] and
+/// [`WinitWindows::set_cursor_grab`], reasserting the cursor icon after grab changes.
+pub(crate) fn applied_cursor_changes(
+    mut windows: Query<(&mut Window, &mut Cached<Cursor>, &mut Cached<Window>)>,
+    mut winit_windows: NonSendMut<WinitWindows>,
+) {
+    // The changes are already applied, so keep the caches in sync to not apply them again.
+    let visibility_changes = std::mem::take(&mut winit_windows.cursor_visibility_changes);
+    for entity in visibility_changes {
+        let (Ok((mut window, mut cache, mut window_cache)), Some(&visible)) = (
+            windows.get_mut(entity),
+            winit_windows.cursor_visibility.get(&entity),
+        ) else {
+            continue;
+        };
+        if window.cursor.visible != visible {
+            window.cursor.visible = visible;
+        }
+        let cursor = Cursor { visible, ..cache.0 };
+        set_cursor_cache_if_neq(&mut cache, cursor);
+        if window_cache.cursor.visible != visible {
+            window_cache.0.cursor.visible = visible;
+        }
+    }
+
+    let grab_changes = std::mem::take(&mut winit_windows.cursor_grab_changes);
+    for (entity, grab_mode) in grab_changes {
+        let Ok((mut window, mut cache, mut window_cache)) = windows.get_mut(entity) else {
+            continue;
+        };
+        if window.cursor.grab_mode != grab_mode {
+            window.cursor.grab_mode = grab_mode;
+        }
+        let cursor = Cursor {
+            grab_mode,
+            ..cache.0
+        };
+        set_cursor_cache_if_neq(&mut cache, cursor);
+        if window_cache.cursor.grab_mode != grab_mode {
+            window_cache.0.cursor.grab_mode = grab_mode;
+        }
+        if let Some(winit_window) = winit_windows.get_window(entity) {
+            winit_window.set_cursor_icon(converters::convert_cursor_icon(window.cursor.icon));
+        }
+    }
+}
+
+/// Despawns the windows whose close was requested, if [`CloseBehavior::auto_despawn`] is enabled.
+pub(crate) fn close_requested_windows(
+    mut commands: Commands,
+    mut close_requests: EventReader<WindowCloseRequested>,
+    close_behavior: Res<CloseBehavior>,
+) {
+    for event in close_requests.read() {
+        if !close_behavior.auto_despawn {
+            continue;
+        }
+        // Windows may already be despawned by `bevy_window`'s `close_when_requested`.
+        if let Some(mut entity_commands) = commands.get_entity(event.window) {
+            entity_commands.despawn();
+        }
+    }
+}
+
+/// Requests a redraw when a [`Window`] changed and
+/// [`WinitSettings::redraw_on_window_change`] is enabled.
+pub(crate) fn redraw_on_window_changes<F: QueryFilter>(
+    changed_windows: Query<(), (Changed<Window>, F)>,
+    settings: Res<WinitSettings>,
+    mut redraw_requests: EventWriter<RequestRedraw>,
+) {
+    if settings.redraw_on_window_change && !changed_windows.is_empty() {
+        redraw_requests.send(RequestRedraw);
+    }
+}
+
+/// Runs the closures queued in [`WinitWindowCommands`] on their winit windows.
+pub(crate) fn winit_window_commands(
+    commands: Res<WinitWindowCommands>,
+    winit_windows: NonSend<WinitWindows>,
+) {
+    for (entity, command) in commands.take() {
+        if let Some(winit_window) = winit_windows.get_window(entity) {
+            command(winit_window);
+        }
+    }
+}
+
+/// Sends the [`RequestScreenshot`] events queued with [`WinitWindows::request_screenshot`].
+pub(crate) fn screenshot_requests(
+    mut winit_windows: NonSendMut<WinitWindows>,
+    mut screenshot_events: EventWriter<RequestScreenshot>,
+) {
+    screenshot_events.send_batch(std::mem::take(&mut winit_windows.screenshot_requests));
+}
+
+/// Run condition that pauses change propagation between [`WinitWindows::begin_batch`] and
+/// [`WinitWindows::end_batch`].
+pub(crate) fn not_batching(winit_windows: NonSend<WinitWindows>) -> bool {
+    !winit_windows.batching
+}
+
+/// Propagates changes from [`WindowExtras`] to the [`winit`] backend.
+#[allow(clippy::type_complexity)]
+pub(crate) fn changed_window_extras<F: QueryFilter>(
+    mut changed_extras: Query<
+        (
+            Entity,
+            &Window,
+            &mut WindowExtras,
+            &mut Cached<WindowExtras>,
+        ),
+        F,
+    >,
+    winit_windows: NonSend<WinitWindows>,
+) {
+    for (entity, window, mut extras, mut cache) in &mut changed_extras {
+        let force = winit_windows.invalidated.contains(&entity);
+        if !force && !extras.is_changed() {
+            continue;
+        }
+
+        let Some(winit_window) = winit_windows.get_window(entity) else {
+            continue;
+        };
+
+        if extras.parent != cache.parent {
+            extras.parent = cache.parent;
+            warn!("Changing the parent of window {entity:?} after it is created is not supported.");
+        }
+
+        // Forced changes already re-applied the constraints in `changed_windows`, and
+        // non-resizable windows keep their size pinned regardless of the constraints.
+        if window.resizable
+            && extras.physical_resize_constraints != cache.physical_resize_constraints
+        {
+            apply_resize_constraints(
+                winit_window,
+                &window.resize_constraints,
+                extras.physical_resize_constraints,
+            );
+        }
+
+        if force || extras.resize_increments != cache.resize_increments {
+            winit_window.set_resize_increments(extras.resize_increments.map(to_logical_size));
+        }
+
+        if force || extras.state != cache.state {
+            if let Some(state) = extras.state {
+                apply_window_state(winit_window, state);
+            }
+        }
+
+        if force || extras.blur != cache.blur {
+            winit_window.set_blur(extras.blur);
+        }
+
+        cache.set_if_neq(Cached(extras.clone()));
+    }
+}
+
+/// Propagates changes to window hooks with [`WindowHook::changed_hook`].
+///
+/// Hooks of windows invalidated with [`WinitWindows::invalidate_cache`] have
+/// [`WindowHook::window_hook`] called again instead, since there's no applied state left to
+/// compare against.
+#[allow(clippy::type_complexity)]
+pub(crate) fn changed_hooks<T: WindowHook, F: QueryFilter>(
+    mut changed_hooks: Query<(Entity, &Window, &mut T, &mut Cached<T>), F>,
+    winit_windows: NonSendMut<WinitWindows>,
+) {
+    for (entity, window, mut data, mut cache) in &mut changed_hooks {
+        let force = winit_windows.invalidated.contains(&entity);
+        if !force && !data.is_changed() {
+            continue;
+        }
+        if let Some(winit_window) = winit_windows.get_window(entity) {
+            if force {
+                data.window_hook(window, winit_window);
+            } else if data.needs_apply(&cache) {
+                data.changed_hook(winit_window, &cache);
+            } else {
+                continue;
+            }
+            cache.0 = data.clone();
+        }
+    }
+}
+
+/// Forgets the windows invalidated with [`WinitWindows::invalidate_cache`], once every system
+/// re-applying them has run.
+pub(crate) fn clear_invalidated(mut winit_windows: NonSendMut<WinitWindows>) {
+    winit_windows.invalidated.clear();
+}
+
+/// Calls [`WindowHook::tick_hook`] for every window with a hook.
+pub(crate) fn tick_hooks<T: WindowHook, F: QueryFilter>(
+    mut hooks: Query<(Entity, &mut T), F>,
+    winit_windows: NonSend<WinitWindows>,
+) {
+    for (entity, mut hook) in &mut hooks {
+        if let Some(winit_window) = winit_windows.get_window(entity) {
+            hook.bypass_change_detection()
+                .tick_hook(entity, winit_window);
+        }
+    }
+}
+
+pub(crate) fn wants_tick<T: WindowHook>() -> bool {
+    T::WANTS_TICK
+}
+
+/// Reads the minimized and maximized state of each window back from the [`winit`] backend.
+///
+/// [`WindowMinimized`] and [`WindowMaximized`] events are sent whenever the state differs from
+/// the last known [`WinitWindowState`], regardless of whether the change came from the app or
+/// from the OS.
+pub(crate) fn changed_window_states(
+    mut window_states: Query<(Entity, &mut WinitWindowState)>,
+    winit_windows: NonSend<WinitWindows>,
+    mut minimized_events: EventWriter<WindowMinimized>,
+    mut maximized_events: EventWriter<WindowMaximized>,
+) {
+    for (entity, mut state) in &mut window_states {
+        let Some(winit_window) = winit_windows.get_window(entity) else {
+            continue;
+        };
+
+        // `is_minimized` is not supported everywhere, keep the last known value in that case.
+        let minimized = winit_window.is_minimized().unwrap_or(state.minimized);
+        if minimized != state.minimized {
+            state.minimized = minimized;
+            minimized_events.send(WindowMinimized {
+                window: entity,
+                minimized,
+            });
+        }
+
+        let maximized = winit_window.is_maximized();
+        if maximized != state.maximized {
+            state.maximized = maximized;
+            maximized_events.send(WindowMaximized {
+                window: entity,
+                maximized,
+            });
+        }
+    }
+}
+
+/// Reads the [`CurrentMonitor`] of each window that moved or changed scale factor back from the
+/// [`winit`] backend, and sends a [`WindowRefreshRateChanged`] event when the window is presented
+/// at a new refresh rate.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn changed_current_monitors(
+    mut current_monitors: Query<(&mut CurrentMonitor, &ExclusiveVideoMode)>,
+    changed_videomodes: Query<Entity, Changed<ExclusiveVideoMode>>,
+    mut moved_events: EventReader<WindowMoved>,
+    mut scale_factor_events: EventReader<WindowBackendScaleFactorChanged>,
+    mut closed_events: EventReader<WindowClosed>,
+    mut refresh_rate_events: EventWriter<WindowRefreshRateChanged>,
+    mut refresh_rates: Local<EntityHashMap<u32>>,
+    winit_windows: NonSend<WinitWindows>,
+) {
+    for event in closed_events.read() {
+        refresh_rates.remove(&event.window);
+    }
+
+    let moved = moved_events.read().map(|event| event.window);
+    let rescaled = scale_factor_events.read().map(|event| event.window);
+    for entity in moved.chain(rescaled).chain(&changed_videomodes) {
+        let Ok((mut current_monitor, videomode)) = current_monitors.get_mut(entity) else {
+            continue;
+        };
+        let Some(monitor) = winit_windows
+            .get_window(entity)
+            .and_then(|winit_window| winit_window.current_monitor())
+        else {
+            continue;
+        };
+        current_monitor.set_if_neq(CurrentMonitor::from_winit(&monitor));
+
+        let refresh_rate = match videomode.0 {
+            Some(videomode) => Some(videomode.refresh_rate_millihertz),
+            None => current_monitor.refresh_rate_millihertz,
+        };
+        let Some(millihertz) = refresh_rate else {
+            continue;
+        };
+        if refresh_rates.insert(entity, millihertz) != Some(millihertz) {
+            refresh_rate_events.send(WindowRefreshRateChanged {
+                window: entity,
+                millihertz,
+            });
+        }
+    }
+}
+
+/// Toggles the hit test of windows with a mask set with [`WinitWindows::set_hit_test_mask`],
+/// depending on whether the cursor is over one of its regions.
+pub(crate) fn hit_test_masks(windows: Query<&Window>, mut winit_windows: NonSendMut<WinitWindows>) {
+    let mut toggled = Vec::new();
+    for (&entity, regions) in &winit_windows.hit_test_masks {
+        let Ok(window) = windows.get(entity) else {
+            continue;
+        };
+        let masked = winit_windows.hit_test_masked.contains(&entity);
+        // A window that clicks already pass through as a whole is left alone, and a cursor that
+        // isn't over the window can't be over the mask.
+        let over_mask = window.cursor.hit_test
+            && window
+                .cursor_position()
+                .is_some_and(|position| regions.iter().any(|region| region.contains(position)));
+        if over_mask == masked {
+            continue;
+        }
+        let Some(winit_window) = winit_windows.get_window(entity) else {
+            continue;
+        };
+        if masked && !window.cursor.hit_test {
+            // `changed_cursors` already disabled the hit test of the whole window.
+            toggled.push((entity, false));
+            continue;
+        }
+        match winit_window.set_cursor_hittest(!over_mask) {
+            Ok(()) => toggled.push((entity, over_mask)),
+            Err(err) => warn!(
+                "Could not set cursor hit test for window {:?}: {:?}",
+                window.title, err
+            ),
+        }
+    }
+
+    for (entity, masked) in toggled {
+        if masked {
+            winit_windows.hit_test_masked.insert(entity);
+        } else {
+            winit_windows.hit_test_masked.remove(&entity);
+        }
+    }
+}
+
+/// Forwards the pending on-screen keyboard requests to [`WindowHook::request_soft_keyboard_hook`].
+pub(crate) fn soft_keyboard_requests<T: WindowHook>(
+    mut hooks: Query<&mut T>,
+    mut winit_windows: NonSendMut<WinitWindows>,
+) {
+    let requests = std::mem::take(&mut winit_windows.soft_keyboard_requests);
+    for (entity, visible) in requests {
+        let (Ok(mut hook), Some(winit_window)) =
+            (hooks.get_mut(entity), winit_windows.get_window(entity))
+        else {
+            continue;
+        };
+        hook.bypass_change_detection()
+            .request_soft_keyboard_hook(entity, winit_window, visible);
+    }
+}
+
+/// Forwards created windows and applied [`Window::cursor`] changes to
+/// [`WindowHook::cursor_policy_hook`].
+pub(crate) fn cursor_policy_requests<T: WindowHook>(
+    mut hooks: Query<(&mut T, &Window)>,
+    mut winit_windows: NonSendMut<WinitWindows>,
+) {
+    let requests = std::mem::take(&mut winit_windows.cursor_policy_requests);
+    for entity in requests {
+        let (Ok((mut hook, window)), Some(winit_window)) =
+            (hooks.get_mut(entity), winit_windows.get_window(entity))
+        else {
+            continue;
+        };
+        // Forwarding isn't a change to the hook, so don't make `changed_hooks` re-apply it.
+        hook.bypass_change_detection()
+            .cursor_policy_hook(entity, winit_window, &window.cursor);
+    }
+}
+
+/// Forwards the fullscreen transitions applied to the [`winit`] backend to
+/// [`WindowHook::fullscreen_changed_hook`].
+pub(crate) fn fullscreen_changes<T: WindowHook>(
+    mut hooks: Query<&mut T>,
+    mut winit_windows: NonSendMut<WinitWindows>,
+) {
+    let changes = std::mem::take(&mut winit_windows.fullscreen_changes);
+    for (entity, mode) in changes {
+        let (Ok(mut hook), Some(winit_window)) =
+            (hooks.get_mut(entity), winit_windows.get_window(entity))
+        else {
+            continue;
+        };
+        hook.bypass_change_detection()
+            .fullscreen_changed_hook(winit_window, mode);
+    }
+}
+
+/// Moves cursors to the targets of [`CursorWarp`] requests, removing the requests.
+///
+/// The observed cursor position of the [`Window`] is updated right away, since not every platform
+/// reports programmatic cursor moves.
+pub(crate) fn cursor_warps(
+    mut commands: Commands,
+    mut windows: Query<(Entity, &mut Window, &CursorWarp)>,
+    winit_windows: NonSend<WinitWindows>,
+) {
+    for (entity, mut window, warp) in &mut windows {
+        commands.entity(entity).remove::<CursorWarp>();
+        let Some(winit_window) = winit_windows.get_window(entity) else {
+            continue;
+        };
+
+        let physical_position = warp.target.as_dvec2() * window.scale_factor() as f64;
+        if let Err(err) = winit_window.set_cursor_position(PhysicalPosition::new(
+            physical_position.x,
+            physical_position.y,
+        )) {
+            error!("could not set cursor position: {:?}", err);
+            continue;
+        }
+        window.set_physical_cursor_position(Some(physical_position));
+    }
+}
+
+/// Moves cursors back into the rectangles set with [`WinitWindows::confine_cursor_to_rect`].
+pub(crate) fn confine_cursors(
+    mut windows: Query<&mut Window>,
+    winit_windows: NonSend<WinitWindows>,
+) {
+    for (&entity, rect) in &winit_windows.cursor_confinements {
+        let Ok(mut window) = windows.get_mut(entity) else {
+            continue;
+        };
+        if window.cursor.grab_mode == CursorGrabMode::Locked || !window.cursor.hit_test {
+            continue;
+        }
+        let Some(position) = window.cursor_position() else {
+            continue;
+        };
+        if rect.contains(position) {
+            continue;
+        }
+        let Some(winit_window) = winit_windows.get_window(entity) else {
+            continue;
+        };
+
+        let clamped = position.clamp(rect.min, rect.max);
+        let physical_position = clamped.as_dvec2() * window.scale_factor() as f64;
+        if let Err(err) = winit_window.set_cursor_position(PhysicalPosition::new(
+            physical_position.x,
+            physical_position.y,
+        )) {
+            error!("could not confine cursor position: {:?}", err);
+            continue;
+        }
+
+        window.set_physical_cursor_position(Some(physical_position));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::prelude::{Added, Event, Events, Schedule, With, Without};
+    use bevy_ecs::system::CommandQueue;
+    use bevy_ecs::world::World;
+    use bevy_math::{DVec2, IVec2};
+    use bevy_window::{CursorIcon, WindowPosition};
+
+    /// A world with the resources and events the systems use, to run them with a [`Schedule`].
+    ///
+    /// It has no winit windows, since those need a display, so this only covers what the systems
+    /// do to the ECS, e.g. for windows that aren't realized or have just been destroyed.
+    fn test_world() -> World {
+        let mut world = World::new();
+        world.insert_non_send_resource(WinitWindows::default());
+        world.init_resource::<WinitSettings>();
+        world.init_resource::<CloseBehavior>();
+        world.init_resource::<Events<WindowCloseRequested>>();
+        world.init_resource::<Events<WindowClosed>>();
+        world.init_resource::<Events<RequestRedraw>>();
+        world
+    }
+
+    /// Takes the `E` events sent so far.
+    fn drain_events<E: Event>(world: &mut World) -> Vec<E> {
+        world.resource_mut::<Events<E>>().drain().collect()
+    }
+
+    #[test]
+    fn unchanged_caches_are_not_marked_changed() {
+        let mut world = World::new();
+        let entity = world
+            .spawn((Cached(Window::default()), Cached(Cursor::default())))
+            .id();
+        world.clear_trackers();
+
+        let mut entity = world.entity_mut(entity);
+        let mut cache = entity.get_mut::<Cached<Window>>().unwrap();
+        set_window_cache_if_neq(&mut cache, &Window::default());
+        assert!(!cache.is_changed());
+        set_window_cache_if_neq(
+            &mut cache,
+            &Window {
+                title: "changed".to_owned(),
+                ..Default::default()
+            },
+        );
+        assert!(cache.is_changed());
+        assert_eq!(cache.title, "changed");
+
+        let mut cache = entity.get_mut::<Cached<Cursor>>().unwrap();
+        set_cursor_cache_if_neq(&mut cache, Cursor::default());
+        assert!(!cache.is_changed());
+        set_cursor_cache_if_neq(
+            &mut cache,
+            Cursor {
+                visible: false,
+                ..Default::default()
+            },
+        );
+        assert!(cache.is_changed());
+    }
+
+    #[test]
+    fn parents_are_created_before_their_children() {
+        let [a, b, c, d, e] = [1, 2, 3, 4, 5].map(Entity::from_raw);
+        let order = creation_order(vec![
+            // `a` is embedded in `c`, which is embedded in `b`.
+            (a, Some(c), Some(CreationOrder(0))),
+            (b, None, None),
+            (c, Some(b), None),
+            // `d`'s parent already exists, so it competes with the top-level windows.
+            (d, Some(Entity::from_raw(100)), Some(CreationOrder(2))),
+            (e, None, Some(CreationOrder(1))),
+        ]);
+        assert_eq!(order, vec![e, d, b, c, a]);
+
+        // Children of existing windows fall back to their entity index like any other window.
+        let order = creation_order(vec![
+            (b, None, None),
+            (a, Some(Entity::from_raw(100)), None),
+        ]);
+        assert_eq!(order, vec![a, b]);
+
+        // Cycles don't hang.
+        let order = creation_order(vec![(a, Some(b), None), (b, Some(a), None)]);
+        assert_eq!(order.len(), 2);
+    }
+
+    #[test]
+    fn cursor_only_changes_are_left_to_changed_cursors() {
+        let cache = Window::default();
+        let mut window = Window {
+            cursor: Cursor {
+                icon: CursorIcon::Pointer,
+                grab_mode: CursorGrabMode::Locked,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(!window_changed_beyond_cursor(&window, &cache));
+        assert!(cursor_changed(&window.cursor, &cache.cursor));
+
+        window.set_maximized(true);
+        assert!(window_changed_beyond_cursor(&window, &cache));
+        // Peeking must not consume the request.
+        assert!(window_changed_beyond_cursor(&window, &cache));
+
+        let window = Window {
+            title: "changed".to_owned(),
+            ..Default::default()
+        };
+        assert!(window_changed_beyond_cursor(&window, &cache));
+        assert!(!cursor_changed(&window.cursor, &cache.cursor));
+    }
+
+    #[test]
+    fn cursor_icon_is_reasserted_after_grab_changes() {
+        let ungrabbed = Cursor {
+            icon: CursorIcon::Crosshair,
+            ..Default::default()
+        };
+        let grabbed = Cursor {
+            grab_mode: CursorGrabMode::Locked,
+            ..ungrabbed
+        };
+        let reasserted = CursorUpdates {
+            grab_mode: true,
+            icon: true,
+            ..Default::default()
+        };
+        assert_eq!(CursorUpdates::new(&grabbed, &ungrabbed, false), reasserted);
+        assert_eq!(CursorUpdates::new(&ungrabbed, &grabbed, false), reasserted);
+        assert_eq!(
+            CursorUpdates::new(&ungrabbed, &ungrabbed, false),
+            CursorUpdates::default()
+        );
+    }
+
+    #[test]
+    fn cursor_changes_without_a_warp_dont_move_the_cursor() {
+        let cache = Window::default();
+        let mut window = cache.clone();
+        window.cursor.icon = CursorIcon::Pointer;
+        window.set_physical_cursor_position(Some(DVec2::new(10.0, 20.0)));
+        // The observed position is neither applied by `changed_windows` nor `changed_cursors`;
+        // only `cursor_warps` moves the cursor, for windows with a `CursorWarp`.
+        assert!(!window_changed_beyond_cursor(&window, &cache));
+        assert!(cursor_changed(&window.cursor, &cache.cursor));
+        assert!(!cursor_changed(
+            &window.cursor,
+            &Cursor {
+                icon: CursorIcon::Pointer,
+                ..cache.cursor
+            }
+        ));
+    }
+
+    #[test]
+    fn hit_test_is_reapplied_when_decorations_toggle() {
+        let mut cache = Window::default();
+        let mut window = cache.clone();
+        let mut decorated = true;
+        assert!(!decorations_need_apply(&window, &cache, false, decorated));
+
+        for decorations in [false, true] {
+            window.decorations = decorations;
+            assert!(decorations_need_apply(&window, &cache, false, decorated));
+            decorated = decorations;
+            cache = window.clone();
+            assert!(!decorations_need_apply(&window, &cache, false, decorated));
+        }
+    }
+
+    #[test]
+    fn geometry_is_held_back_while_minimized() {
+        let cache = Window::default();
+        let mut window = cache.clone();
+        window.resolution.set(640.0, 480.0);
+
+        let minimized = GeometryUpdates::new(&window, &cache, false, true, false);
+        assert_eq!(
+            minimized,
+            GeometryUpdates {
+                hold_back: true,
+                ..Default::default()
+            }
+        );
+
+        // The cache was updated while minimized, so only the pending flag remembers the resize.
+        let cache = window.clone();
+        let restored = GeometryUpdates::new(&window, &cache, false, false, true);
+        assert_eq!(
+            restored,
+            GeometryUpdates {
+                constraints: true,
+                position: true,
+                size: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            GeometryUpdates::new(&window, &cache, false, false, false),
+            GeometryUpdates::default()
+        );
+    }
+
+    #[test]
+    fn moves_and_resizes_in_one_tick_are_applied_together() {
+        let cache = Window::default();
+        let mut window = cache.clone();
+        window.position = WindowPosition::At(IVec2::new(100, 200));
+        window.resolution.set_physical_resolution(800, 600);
+
+        // Both are applied in one pass, the position before the size.
+        assert_eq!(
+            GeometryUpdates::new(&window, &cache, false, false, false),
+            GeometryUpdates {
+                position: true,
+                size: true,
+                ..Default::default()
+            }
+        );
+
+        // The position is clamped with the size the window is resized to, not its current one.
+        let outer = PhysicalSize::new(1290, 750);
+        let inner = PhysicalSize::new(1280, 720);
+        assert_eq!(
+            outer_size_after_resize(outer, inner, &window.resolution),
+            PhysicalSize::new(810, 630)
+        );
+    }
+
+    #[test]
+    fn created_windows_announce_their_actual_size() {
+        let entity = Entity::from_raw(1);
+        // SAFETY: The id is only compared, never passed to `winit`.
+        let window_id = unsafe { winit::window::WindowId::dummy() };
+        let mut window = Window::default();
+        let (resized, backend_created) = initial_size_events(
+            entity,
+            &mut window,
+            window_id,
+            PhysicalSize::new(1000, 600),
+            2.0,
+        );
+        assert_eq!(
+            resized,
+            WindowResized {
+                window: entity,
+                width: 500.0,
+                height: 300.0,
+            }
+        );
+        assert_eq!(
+            backend_created,
+            WindowBackendCreated {
+                window: entity,
+                window_id,
+                scale_factor: 2.0,
+            }
+        );
+        assert_eq!(window.physical_width(), 1000);
+
+        // Invalid scale factors are replaced before being announced.
+        let (_, backend_created) = initial_size_events(
+            entity,
+            &mut window,
+            window_id,
+            PhysicalSize::new(1000, 600),
+            f64::NAN,
+        );
+        assert_eq!(backend_created.scale_factor, 1.0);
+        assert_eq!(window.width(), 1000.0);
+    }
+
+    #[test]
+    fn decorations_are_restored_when_leaving_fullscreen() {
+        let mut window = Window {
+            mode: WindowMode::BorderlessFullscreen,
+            ..Default::default()
+        };
+        let cache = window.clone();
+        let decorated = true;
+
+        // Toggled while fullscreen, so it's deferred, but remembered in the cache.
+        window.decorations = false;
+        assert!(!decorations_need_apply(&window, &cache, false, decorated));
+        let cache = window.clone();
+
+        window.mode = WindowMode::Windowed;
+        assert!(decorations_need_apply(&window, &cache, false, decorated));
+        // Nothing to restore if the winit window already matches.
+        assert!(!decorations_need_apply(&window, &cache, false, false));
+    }
+
+    #[test]
+    fn windowed_sizes_survive_fullscreen() {
+        let entity = Entity::from_raw(1);
+        let mut windowed_sizes = EntityHashMap::default();
+        let windowed = PhysicalSize::new(800, 600);
+        let fullscreen = PhysicalSize::new(1920, 1080);
+
+        assert_eq!(
+            track_windowed_size(&mut windowed_sizes, entity, false, true, windowed),
+            None
+        );
+        // Switching to another fullscreen mode keeps the size from before the first one.
+        assert_eq!(
+            track_windowed_size(&mut windowed_sizes, entity, true, true, fullscreen),
+            None
+        );
+        assert_eq!(
+            track_windowed_size(&mut windowed_sizes, entity, true, false, fullscreen),
+            Some(windowed)
+        );
+        assert!(windowed_sizes.is_empty());
+    }
+
+    #[test]
+    fn re_added_windows_are_created_again() {
+        let mut world = test_world();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(despawn_windows::<crate::NoHook>);
+        // `create_windows` only picks up windows without a cache.
+        let mut pending_creation =
+            world.query_filtered::<Entity, (With<Window>, Without<Cached<Window>>)>();
+        let window = world
+            .spawn((Window::default(), Cached(Window::default())))
+            .id();
+
+        // Removed and re-added within one update, so the winit window is kept.
+        world.entity_mut(window).remove::<Window>();
+        world.entity_mut(window).insert(Window::default());
+        schedule.run(&mut world);
+        world.clear_trackers();
+        assert_eq!(pending_creation.iter(&world).count(), 0);
+        assert!(drain_events::<WindowClosed>(&mut world).is_empty());
+
+        // Removed in one update and re-added in the next, so it's created again.
+        world.entity_mut(window).remove::<Window>();
+        schedule.run(&mut world);
+        world.clear_trackers();
+        assert_eq!(
+            drain_events::<WindowClosed>(&mut world),
+            [WindowClosed { window }]
+        );
+        world.entity_mut(window).insert(Window::default());
+        schedule.run(&mut world);
+        assert_eq!(pending_creation.iter(&world).collect::<Vec<_>>(), [window]);
+    }
+
+    #[test]
+    fn stale_raw_handles_are_replaced_by_new_ones() {
+        use raw_window_handle::{
+            RawDisplayHandle, RawWindowHandle, WebDisplayHandle, WebWindowHandle,
+        };
+        let handle = |id| RawHandleWrapper {
+            window_handle: RawWindowHandle::Web(WebWindowHandle::new(id)),
+            display_handle: RawDisplayHandle::Web(WebDisplayHandle::new()),
+        };
+
+        let mut world = World::new();
+        let window = world.spawn(handle(1)).id();
+        world.clear_trackers();
+
+        // The backend was reset, and the window created again.
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        insert_raw_handle(&mut commands.entity(window), handle(2), true);
+        queue.apply(&mut world);
+
+        // Renderers pick up new surfaces for added handles.
+        let mut added = world.query_filtered::<&RawHandleWrapper, Added<RawHandleWrapper>>();
+        let handles: Vec<_> = added.iter(&world).collect();
+        assert_eq!(handles.len(), 1);
+        assert_eq!(
+            handles[0].window_handle,
+            RawWindowHandle::Web(WebWindowHandle::new(2))
+        );
+    }
+
+    #[test]
+    fn restored_windows_are_not_corrected_after_creation() {
+        let mut window = Window {
+            position: WindowPosition::At(IVec2::new(100, 200)),
+            resolution: WindowResolution::new(1280.0, 720.0),
+            ..Default::default()
+        };
+        window.set_maximized(true);
+        // `create_window` builds the window maximized, and the cache is seeded from it.
+        assert_eq!(window.internal.take_maximize_request(), Some(true));
+        let cache = window.clone();
+
+        assert!(!window_changed_beyond_cursor(&window, &cache));
+        assert_eq!(
+            GeometryUpdates::new(&window, &cache, false, false, false),
+            GeometryUpdates::default()
+        );
+    }
+
+    #[test]
+    fn buttons_are_changed_while_undecorated() {
+        // Adding the title bar after its buttons changed, and removing it before.
+        assert_eq!(
+            ButtonsUpdate::new(true, true, true),
+            ButtonsUpdate::BeforeDecorations
+        );
+        assert_eq!(
+            ButtonsUpdate::new(true, true, false),
+            ButtonsUpdate::AfterDecorations
+        );
+        assert_eq!(
+            ButtonsUpdate::new(true, false, true),
+            ButtonsUpdate::AfterDecorations
+        );
+        assert_eq!(
+            ButtonsUpdate::new(false, true, true),
+            ButtonsUpdate::Unchanged
+        );
+    }
+
+    #[test]
+    fn close_requests_despawn_windows_if_enabled() {
+        for auto_despawn in [false, true] {
+            let mut world = test_world();
+            world.insert_resource(CloseBehavior { auto_despawn });
+            let mut schedule = Schedule::default();
+            schedule.add_systems(close_requested_windows);
+            let window = world.spawn(Window::default()).id();
+
+            world.send_event(WindowCloseRequested { window });
+            schedule.run(&mut world);
+            assert_eq!(world.get_entity(window).is_none(), auto_despawn);
+        }
+    }
+
+    #[test]
+    fn window_changes_request_redraws_if_enabled() {
+        let mut world = test_world();
+        world
+            .resource_mut::<WinitSettings>()
+            .redraw_on_window_change = true;
+        let mut schedule = Schedule::default();
+        schedule.add_systems(redraw_on_window_changes::<()>);
+        let window = world.spawn(Window::default()).id();
+        schedule.run(&mut world);
+        world.clear_trackers();
+        assert_eq!(drain_events::<RequestRedraw>(&mut world).len(), 1);
+
+        schedule.run(&mut world);
+        world.clear_trackers();
+        assert!(drain_events::<RequestRedraw>(&mut world).is_empty());
+
+        world.get_mut::<Window>(window).unwrap().title = "Changed".to_owned();
+        schedule.run(&mut world);
+        assert_eq!(drain_events::<RequestRedraw>(&mut world).len(), 1);
+    }
 }
@@ -0,0 +1,171 @@
+use bevy_ecs::{component::Component, entity::Entity};
+use bevy_math::{IVec2, UVec2, Vec2};
+use bevy_window::WindowTheme;
+
+use crate::{from_physical_position, from_physical_size, VideoModeInfo};
+
+/// The minimized and maximized state of a window, as last reported by `winit`.
+///
+/// This component is inserted when the window is created and kept up to date every frame.
+/// It is read-only: use [`Window::set_minimized`](bevy_window::Window::set_minimized) and
+/// [`Window::set_maximized`](bevy_window::Window::set_maximized) to change the state.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WinitWindowState {
+    /// Whether the window is minimized.
+    ///
+    /// Some platforms (e.g. Wayland) can't report this, in which case it stays `false`.
+    pub minimized: bool,
+    /// Whether the window is maximized.
+    pub maximized: bool,
+}
+
+impl WinitWindowState {
+    pub(crate) fn from_winit(winit_window: &winit::window::Window) -> Self {
+        Self {
+            minimized: winit_window.is_minimized().unwrap_or(false),
+            maximized: winit_window.is_maximized(),
+        }
+    }
+}
+
+/// The monitor a window is on, as last reported by `winit`.
+///
+/// This component is inserted when the window is created and updated whenever the window moves or
+/// its scale factor changes. It keeps the last known monitor while `winit` can't determine one.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct CurrentMonitor {
+    /// The human-readable name of the monitor, if available.
+    pub name: Option<String>,
+    /// The resolution of the monitor, in physical pixels.
+    pub size: UVec2,
+    /// The position of the top-left corner of the monitor on the desktop, in physical pixels.
+    pub position: IVec2,
+    /// The scale factor of the monitor.
+    pub scale_factor: f64,
+    /// The refresh rate of the monitor's current video mode, in millihertz, if available.
+    pub refresh_rate_millihertz: Option<u32>,
+}
+
+impl CurrentMonitor {
+    pub(crate) fn from_winit(monitor: &winit::monitor::MonitorHandle) -> Self {
+        Self {
+            name: monitor.name(),
+            size: from_physical_size(monitor.size()),
+            position: from_physical_position(monitor.position()),
+            scale_factor: monitor.scale_factor(),
+            refresh_rate_millihertz: monitor.refresh_rate_millihertz(),
+        }
+    }
+}
+
+/// The theme a window is actually using, as last reported by `winit`.
+///
+/// Unlike [`Window::window_theme`](bevy_window::Window::window_theme), which is `None` when the
+/// window follows the system theme, this is the theme the system resolved to. It is inserted when
+/// the window is created and updated whenever the theme changes. It is `None` if the platform
+/// can't report the theme.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EffectiveWindowTheme(pub Option<WindowTheme>);
+
+/// The video mode a window is using in exclusive fullscreen, as applied by `winit`.
+///
+/// This may differ from the size requested through the [`Window`](bevy_window::Window), since
+/// only modes supported by the monitor can be used. It is `None` while the window isn't in
+/// exclusive fullscreen.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExclusiveVideoMode(pub Option<VideoModeInfo>);
+
+/// A request to move the cursor of a window, consumed on the next update.
+///
+/// Writing the cursor position of the [`Window`](bevy_window::Window) only changes the observed
+/// position and doesn't move the cursor: insert this component instead. It is removed once the
+/// request has been handled.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct CursorWarp {
+    /// The position to move the cursor to, in logical pixels relative to the top-left of the
+    /// client area.
+    pub target: Vec2,
+}
+
+/// The order in which windows created in the same update are created, lowest first.
+///
+/// Windows embedded in another window that is created in the same update always come after their
+/// [parent](WindowExtras::parent). Windows with the same order, or without this component, are
+/// created by ascending entity index, after all windows that have it.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CreationOrder(pub u32);
+
+/// A marker for windows whose routine creation and closing logs are emitted at the `trace` level
+/// instead of `info`, e.g. for short-lived windows like tooltips. Warnings and errors are still
+/// logged as usual.
+///
+/// This is read when the window is created.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuietWindow;
+
+/// Extra settings for a window that aren't covered by [`Window`](bevy_window::Window).
+///
+/// Like the [`Window`](bevy_window::Window) itself, these are applied when the window is created
+/// and changes are propagated to the `winit` backend afterwards. Windows without this component
+/// get the default settings.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct WindowExtras {
+    /// The logical size increments the window snaps to when resized by the user, e.g. the size of
+    /// a character cell for terminal emulators.
+    ///
+    /// ## Platform-specific
+    ///
+    /// Only supported on macOS and X11.
+    pub resize_increments: Option<Vec2>,
+    /// Whether the [`resize_constraints`](bevy_window::Window::resize_constraints) of the window
+    /// are in physical pixels rather than logical pixels.
+    ///
+    /// Logical constraints are scaled by the scale factor of the window, which rounds them at
+    /// fractional scale factors. Physical constraints are applied exactly, e.g. for pixel art at
+    /// integer multiples of a base resolution.
+    pub physical_resize_constraints: bool,
+    /// Whether the background of a [`transparent`](bevy_window::Window::transparent) window is
+    /// blurred.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Android / iOS / X11 / Web / Windows:** Unsupported.
+    /// - **Wayland:** Only works with the `org_kde_kwin_blur_manager` protocol.
+    pub blur: bool,
+    /// The window entity this window is embedded in.
+    ///
+    /// The window isn't created until its parent has a `winit` window, and this can't be changed
+    /// after the window is created. If the parent entity doesn't exist, the window isn't created
+    /// and a [`WindowCreationFailed`](crate::WindowCreationFailed) event is sent.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / X11:** The window is confined to the client area of its parent.
+    /// - **Android / iOS / Wayland / Web:** Unsupported.
+    pub parent: Option<Entity>,
+    /// The state the window should be in.
+    ///
+    /// Unlike [`Window::set_maximized`](bevy_window::Window::set_maximized) and
+    /// [`Window::set_minimized`](bevy_window::Window::set_minimized), which are one-shot requests,
+    /// this is applied when the window is created, whenever it changes, and when the window is
+    /// invalidated with [`WinitWindows::invalidate_cache`](crate::WinitWindows::invalidate_cache).
+    /// Changes made by the user in between are reported through [`WinitWindowState`] and aren't
+    /// reverted. `None` leaves the state to the one-shot requests.
+    pub state: Option<WindowState>,
+}
+
+/// A declared window state, see [`WindowExtras::state`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum WindowState {
+    /// The window is neither maximized nor minimized.
+    #[default]
+    Windowed,
+    /// The window is maximized.
+    Maximized,
+    /// The window is minimized.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Un-minimizing is unsupported.
+    Minimized,
+}
@@ -8,6 +8,63 @@ pub struct WinitSettings {
     pub focused_mode: UpdateMode,
     /// Determines how frequently the application can update when it's out of focus.
     pub unfocused_mode: UpdateMode,
+    /// The minimum number of physical pixels of a window, in each direction, that must remain on a
+    /// monitor when its position is changed at runtime.
+    ///
+    /// Positions that would leave less of the window visible are clamped back onto the nearest
+    /// monitor. `None`, the default, disables clamping; `Some(32)` keeps enough of a window on
+    /// screen to grab it.
+    pub min_visible_window_size: Option<u32>,
+    /// How long the scale factor of a window has to stay the same before it is applied.
+    ///
+    /// A window straddling monitors with different scale factors can get alternating scale factor
+    /// changes while it is moved. Only the last change is applied once no other change arrived for
+    /// this long, instead of relayouting on every change.
+    ///
+    /// Defaults to [`Duration::ZERO`], which applies every change right away. Settled changes can't
+    /// answer the size the OS suggests along with the change: windows with a
+    /// [scale factor override](bevy_window::WindowResolution::scale_factor_override) are resized
+    /// again afterwards, and resizes during the settle time are read with the previous scale
+    /// factor.
+    pub scale_factor_settle_time: Duration,
+    /// Whether a change to a [`Window`](bevy_window::Window) requests a redraw.
+    ///
+    /// With [`Reactive`](UpdateMode::Reactive) or [`ReactiveLowPower`](UpdateMode::ReactiveLowPower)
+    /// and a `wait` of [`Duration::MAX`], the event loop runs with
+    /// [`ControlFlow::Wait`](winit::event_loop::ControlFlow::Wait) and only updates on events.
+    /// Enabling this sends a [`RequestRedraw`](bevy_window::RequestRedraw) whenever a window
+    /// changed, so the loop wakes up for another update that picks up anything the change
+    /// caused instead of idling until the next event.
+    pub redraw_on_window_change: bool,
+    /// Whether windows missing required components are left uncreated, instead of getting the
+    /// missing components with their default values.
+    ///
+    /// Currently the only such component is [`WindowExtras`](crate::WindowExtras). Skipped windows
+    /// log an error, and are created once the missing components are added.
+    pub strict_window_components: bool,
+    /// Whether windows destroyed by the platform while their [`Window`](bevy_window::Window) still
+    /// exists are created again.
+    ///
+    /// A [`WindowClosed`](bevy_window::WindowClosed) event is sent either way. If this is
+    /// `false`, the [`Window`](bevy_window::Window) component is removed from the entity.
+    /// Otherwise a new window is created for it on the next update, with a new
+    /// [`WindowCreated`](bevy_window::WindowCreated) event.
+    pub recreate_lost_windows: bool,
+    /// Where a window is moved when its [`Window::position`](bevy_window::Window::position) is
+    /// changed to one that can't be resolved, e.g. [`Automatic`](bevy_window::WindowPosition::Automatic)
+    /// or centered on a monitor that doesn't exist.
+    pub position_fallback: PositionFallback,
+    /// How many more times the creation of a window is attempted after it failed, e.g. because
+    /// the display server isn't ready yet at startup.
+    ///
+    /// Once the retries are exhausted, a [`WindowCreationFailed`](crate::WindowCreationFailed)
+    /// event is sent and the window isn't attempted again.
+    pub window_creation_retries: u32,
+    /// How long to wait before the first retry of a failed window creation. The delay doubles
+    /// after each further failure.
+    ///
+    /// Retries happen on the first update after the delay elapsed.
+    pub window_creation_retry_delay: Duration,
 }
 
 impl WinitSettings {
@@ -21,6 +78,14 @@ impl WinitSettings {
             unfocused_mode: UpdateMode::ReactiveLowPower {
                 wait: Duration::from_secs_f64(1.0 / 60.0), // 60Hz
             },
+            min_visible_window_size: None,
+            scale_factor_settle_time: Duration::ZERO,
+            redraw_on_window_change: false,
+            strict_window_components: false,
+            recreate_lost_windows: false,
+            position_fallback: PositionFallback::LeaveAsIs,
+            window_creation_retries: 3,
+            window_creation_retry_delay: Duration::from_millis(100),
         }
     }
 
@@ -36,6 +101,14 @@ impl WinitSettings {
             unfocused_mode: UpdateMode::ReactiveLowPower {
                 wait: Duration::from_secs(60),
             },
+            min_visible_window_size: None,
+            scale_factor_settle_time: Duration::ZERO,
+            redraw_on_window_change: false,
+            strict_window_components: false,
+            recreate_lost_windows: false,
+            position_fallback: PositionFallback::LeaveAsIs,
+            window_creation_retries: 3,
+            window_creation_retry_delay: Duration::from_millis(100),
         }
     }
 
@@ -56,11 +129,104 @@ impl Default for WinitSettings {
     }
 }
 
+/// Where a window is moved when its new position can't be resolved, see
+/// [`WinitSettings::position_fallback`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PositionFallback {
+    /// The window stays where it is.
+    #[default]
+    LeaveAsIs,
+    /// The window is centered on the primary monitor, or stays where it is if there is none.
+    CenterOnPrimary,
+    /// The window is moved to the top-left corner of the primary monitor, or of the desktop if
+    /// there is no primary monitor.
+    TopLeft,
+}
+
+/// Decides what happens when the user asks to close a window, e.g. with its close button.
+///
+/// A [`WindowCloseRequested`](bevy_window::WindowCloseRequested) event is always sent. If
+/// `auto_despawn` is `true`, the window entity is then despawned in [`Last`](bevy_app::Last),
+/// unless something else already did. This can be changed at runtime, e.g. to despawn windows
+/// right away again once there are no unsaved changes left.
+///
+/// `auto_despawn` is `false` by default, so this crate changes nothing on its own. Windows are
+/// despawned if either this or
+/// [`WindowPlugin::close_when_requested`](bevy_window::WindowPlugin::close_when_requested) is
+/// enabled, so to route every close through app logic, disable `close_when_requested` and leave
+/// this `false`. Despawning the entity removes its [`WindowHook`](crate::WindowHook) as well, so
+/// [`WindowHook::before_destroy`](crate::WindowHook::before_destroy) can't postpone the
+/// destruction of windows closed either way.
+#[derive(Debug, Clone, Default, Resource)]
+pub struct CloseBehavior {
+    /// Whether windows are despawned when their close is requested.
+    pub auto_despawn: bool,
+}
+
+/// What happens to a `winit` event after a [`WinitEventHooks`] callback saw it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EventAction {
+    /// The event is passed on to the next callback, and then handled by this crate.
+    #[default]
+    Continue,
+    /// The event is dropped, without being seen by later callbacks or handled by this crate.
+    Consume,
+}
+
+/// A callback that sees `winit` events before this crate handles them, see [`WinitEventHooks`].
+pub type WinitEventHook = Box<dyn FnMut(&winit::event::Event<()>) -> EventAction + Send + Sync>;
+
+/// Callbacks run for every `winit` event before this crate handles it, e.g. to capture global
+/// hotkeys or to record events.
+///
+/// Callbacks run in the order they were added, before
+/// [`WindowHook::on_window_event`](crate::WindowHook::on_window_event) and any other handling,
+/// for all events including those that aren't scoped to a window. Consuming an event the runner
+/// depends on, like [`AboutToWait`](winit::event::Event::AboutToWait), stops the app from
+/// updating.
+#[derive(Resource, Default)]
+pub struct WinitEventHooks {
+    hooks: Vec<WinitEventHook>,
+}
+
+impl WinitEventHooks {
+    /// Adds a callback, run after the ones already added.
+    pub fn add(
+        &mut self,
+        hook: impl FnMut(&winit::event::Event<()>) -> EventAction + Send + Sync + 'static,
+    ) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// Runs the callbacks on `event`, stopping at the first one that consumes it.
+    pub(crate) fn pre_event(&mut self, event: &winit::event::Event<()>) -> EventAction {
+        for hook in &mut self.hooks {
+            if hook(event) == EventAction::Consume {
+                return EventAction::Consume;
+            }
+        }
+        EventAction::Continue
+    }
+}
+
+impl std::fmt::Debug for WinitEventHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WinitEventHooks")
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
 /// Determines how frequently an [`App`](bevy_app::App) should update.
 ///
 /// **Note:** This setting is independent of VSync. VSync is controlled by a window's
 /// [`PresentMode`](bevy_window::PresentMode) setting. If an app can update faster than the refresh
 /// rate, but VSync is enabled, the update rate will be indirectly limited by the renderer.
+///
+/// **Note:** In the reactive modes, a change to a [`Window`](bevy_window::Window) doesn't wake
+/// the event loop by itself. It is applied on the next update, and anything the change causes
+/// (e.g. a resize) arrives as a new window event. Enable
+/// [`WinitSettings::redraw_on_window_change`] to run another update right after a change instead.
 #[derive(Debug, Clone, Copy)]
 pub enum UpdateMode {
     /// The [`App`](bevy_app::App) will update over and over, as fast as it possibly can, until an
@@ -71,7 +237,7 @@ pub enum UpdateMode {
     /// - `wait` time has elapsed since the previous update
     /// - a redraw has been requested by [`RequestRedraw`](bevy_window::RequestRedraw)
     /// - new [window](`winit::event::WindowEvent`) or [raw input](`winit::event::DeviceEvent`)
-    /// events have appeared
+    ///   events have appeared
     Reactive {
         /// The approximate time from the start of one update to the next.
         ///
@@ -97,3 +263,22 @@ pub enum UpdateMode {
         wait: Duration,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_factor_changes_apply_right_away_by_default() {
+        assert!(WinitSettings::game().scale_factor_settle_time.is_zero());
+        assert!(WinitSettings::desktop_app()
+            .scale_factor_settle_time
+            .is_zero());
+    }
+
+    #[test]
+    fn window_positions_are_not_clamped_by_default() {
+        assert_eq!(WinitSettings::game().min_visible_window_size, None);
+        assert_eq!(WinitSettings::desktop_app().min_visible_window_size, None);
+    }
+}
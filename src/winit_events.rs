@@ -0,0 +1,105 @@
+use bevy_ecs::{entity::Entity, event::Event};
+
+/// An event that is sent whenever a window is minimized or restored from the minimized state.
+///
+/// This is sent for changes made by the user through the OS as well as for changes requested
+/// through [`Window::set_minimized`](bevy_window::Window::set_minimized).
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowMinimized {
+    /// Window that changed minimized state.
+    pub window: Entity,
+    /// Whether it was minimized (true) or restored (false).
+    pub minimized: bool,
+}
+
+/// An event that is sent whenever a window is maximized or restored from the maximized state.
+///
+/// This is sent for changes made by the user through the OS as well as for changes requested
+/// through [`Window::set_maximized`](bevy_window::Window::set_maximized).
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowMaximized {
+    /// Window that changed maximized state.
+    pub window: Entity,
+    /// Whether it was maximized (true) or restored (false).
+    pub maximized: bool,
+}
+
+/// An event that is sent whenever a window's logical size changes because its scale factor
+/// changed, rather than because it was resized by the user or the app.
+///
+/// A [`WindowResized`](bevy_window::WindowResized) event is still sent alongside this one, so
+/// systems that don't care about the cause keep working.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct WindowBackendResized {
+    /// Window that has changed.
+    pub window: Entity,
+    /// The new logical width of the window.
+    pub width: f32,
+    /// The new logical height of the window.
+    pub height: f32,
+    /// The scale factor that caused the resize.
+    pub scale_factor: f64,
+}
+
+/// An event that is sent alongside [`WindowCreated`](bevy_window::WindowCreated) whenever a new
+/// `winit` window is created, carrying the backend details of that window.
+///
+/// On Android, the native window is destroyed when the app is suspended. Its
+/// [`RawHandleWrapper`](bevy_window::RawHandleWrapper) is removed then, and a new one is inserted
+/// once the app resumes. This event is sent again at that point, without a
+/// [`WindowCreated`](bevy_window::WindowCreated), so renderers can recreate their surface.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct WindowBackendCreated {
+    /// Window that has been created.
+    pub window: Entity,
+    /// The identifier `winit` uses for the window in its events.
+    pub window_id: winit::window::WindowId,
+    /// The initial scale factor of the window.
+    pub scale_factor: f64,
+}
+
+/// An event that is sent whenever the refresh rate a window is presented at changes.
+///
+/// This is the refresh rate of the [exclusive video mode](crate::ExclusiveVideoMode) while the
+/// window is in exclusive fullscreen, and that of its [current monitor](crate::CurrentMonitor)
+/// otherwise. It is first sent once the window is created, then whenever the window moves to a
+/// monitor with a different refresh rate, or enters or leaves exclusive fullscreen. Nothing is sent
+/// while the platform can't report the refresh rate.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowRefreshRateChanged {
+    /// Window whose refresh rate changed.
+    pub window: Entity,
+    /// The new refresh rate, in millihertz.
+    pub millihertz: u32,
+}
+
+/// An event that is sent to request a capture of a window's client area, with
+/// [`WinitWindows::request_screenshot`](crate::WinitWindows::request_screenshot).
+///
+/// This crate doesn't capture anything itself; renderers are expected to read this event and
+/// capture the next frame presented to the window.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct RequestScreenshot {
+    /// Window to capture.
+    pub window: Entity,
+    /// The physical width of the window when the capture was requested.
+    pub physical_width: u32,
+    /// The physical height of the window when the capture was requested.
+    pub physical_height: u32,
+    /// The scale factor of the window when the capture was requested.
+    pub scale_factor: f64,
+}
+
+/// An event that is sent when a `winit` window couldn't be created for a window entity, after
+/// [`WinitSettings::window_creation_retries`](crate::WinitSettings::window_creation_retries)
+/// retries, or because its [parent](crate::WindowExtras::parent) doesn't exist.
+///
+/// The [`Window`](bevy_window::Window) component is left in place, but no further attempts are
+/// made to create it.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct WindowCreationFailed {
+    /// Window that couldn't be created.
+    pub window: Entity,
+    /// The error reported by `winit` for the last attempt, or the missing parent.
+    pub error: String,
+}
@@ -1,24 +1,204 @@
-use bevy_ecs::component::Component;
-use bevy_window::Window;
+use std::marker::PhantomData;
+
+use bevy_a11y::accesskit::NodeBuilder;
+use bevy_app::{App, Last, Plugin};
+use bevy_ecs::{
+    component::Component, entity::Entity, query::QueryFilter, schedule::IntoSystemConfigs,
+};
+use bevy_window::{exit_on_all_closed, Cursor, Window, WindowMode};
 use winit::window::WindowBuilder;
 
+use crate::system::{
+    applied_cursor_changes, applied_video_modes, changed_current_monitors, changed_cursors,
+    changed_hooks, changed_window_extras, changed_window_states, changed_windows,
+    clear_invalidated, confine_cursors, cursor_policy_requests, cursor_warps, despawn_windows,
+    fullscreen_changes, hit_test_masks, not_batching, redraw_on_window_changes,
+    soft_keyboard_requests, tick_hooks, wants_tick, winit_window_commands,
+};
+
+/// The attributes a [`winit::window::Window`] is created with.
+///
+/// This is [`WindowBuilder`] for the `winit` version currently in use. Newer `winit` versions
+/// replace it with `WindowAttributes`, and this alias will follow, so hooks written against
+/// [`WindowHook::attributes_hook`] keep working through that change as long as they only use the
+/// `with_*` methods both types share.
+pub type WindowAttributes = WindowBuilder;
+
 /// Types that represent extra data to be stored with a window.
 #[allow(unused_variables)]
 pub trait WindowHook: Clone + Component {
+    /// Whether [`WindowHook::tick_hook`] is called every update.
+    ///
+    /// Hooks that don't override [`WindowHook::tick_hook`] should leave this `false`, so no
+    /// system iterates over them.
+    const WANTS_TICK: bool = false;
+
+    /// Modifies the [`WindowAttributes`] of a window with extra configuration before it is
+    /// created.
+    ///
+    /// Calls [`WindowHook::builder_hook`] by default, so existing hooks keep working.
+    fn attributes_hook(&self, window: &Window, attributes: WindowAttributes) -> WindowAttributes {
+        self.builder_hook(window, attributes)
+    }
     /// Modifies a [`winit::window::WindowBuilder`] with extra configuration.
-    fn builder_hook(&self, window: &Window, winit_builder: WindowBuilder) -> WindowBuilder;
+    ///
+    /// Prefer [`WindowHook::attributes_hook`], this is only kept until `winit` drops
+    /// [`WindowBuilder`].
+    fn builder_hook(&self, window: &Window, winit_builder: WindowBuilder) -> WindowBuilder {
+        winit_builder
+    }
     /// Modifies a [`winit::window::Window`] with extra configuration.
     fn window_hook(&self, window: &Window, winit_window: &winit::window::Window) {}
     /// Updates a [`winit::window::Window`] when the corresponding [`WindowHook`] has changed.
     fn changed_hook(&mut self, winit_window: &winit::window::Window, cached: &Self) {}
+    /// Whether a change to the hook needs to be applied with [`WindowHook::changed_hook`].
+    ///
+    /// `cached` is the hook as it was when last applied. Returning `false` skips
+    /// [`WindowHook::changed_hook`] and keeps `cached` as it is, so the next change is still
+    /// compared against the last applied state.
+    fn needs_apply(&self, cached: &Self) -> bool {
+        true
+    }
+    /// Whether the window can be created yet.
+    ///
+    /// While this returns `false` the window is not created, and no
+    /// [`WindowCreated`](bevy_window::WindowCreated) event is sent. Creation is retried on every
+    /// following update until the hook is ready.
+    fn ready(&self) -> bool {
+        true
+    }
+    /// Called before the [`winit::window::Window`] of a removed [`Window`] is destroyed.
+    ///
+    /// Returning `false` postpones the destruction, and the
+    /// [`WindowClosed`](bevy_window::WindowClosed) event, to a later update. This is only called
+    /// while the hook component itself still exists, so despawning the entity destroys the
+    /// window right away.
+    fn before_destroy(&mut self, entity: Entity) -> bool {
+        true
+    }
+    /// Shows or hides the on-screen keyboard for a [`winit::window::Window`].
+    ///
+    /// Called after [`WinitWindows::request_soft_keyboard`](crate::WinitWindows::request_soft_keyboard),
+    /// and whenever [`Window::ime_enabled`] changes. `winit` has no API to show the soft keyboard
+    /// itself, so this is where platform-specific code (e.g. through JNI on Android) belongs.
+    fn request_soft_keyboard_hook(
+        &mut self,
+        entity: Entity,
+        winit_window: &winit::window::Window,
+        visible: bool,
+    ) {
+    }
+    /// Applies platform-specific cursor behavior to a [`winit::window::Window`], e.g. whether the
+    /// OS hides the cursor while typing.
+    ///
+    /// Called once the window is created, and whenever changes to [`Window::cursor`] were applied.
+    /// `winit` has no API for cursor policies like auto-hide on any platform, so this is where
+    /// platform-specific code belongs, e.g. through the raw window handle or `NSCursor` on macOS.
+    /// It does nothing by default.
+    fn cursor_policy_hook(
+        &mut self,
+        entity: Entity,
+        winit_window: &winit::window::Window,
+        cursor: &Cursor,
+    ) {
+    }
+    /// Called after a [`winit::window::Window`] entered, left or switched fullscreen.
+    ///
+    /// `mode` is the mode that was applied, which can differ from the requested one if it wasn't
+    /// supported (e.g. exclusive fullscreen falling back to borderless).
+    fn fullscreen_changed_hook(&mut self, winit_window: &winit::window::Window, mode: WindowMode) {}
+    /// Called for every [`WindowEvent`](winit::event::WindowEvent) received for this window.
+    ///
+    /// This fires before the crate's built-in handling of the event, so it sees events the crate
+    /// doesn't otherwise expose, but after the [`WinitEventHooks`](crate::WinitEventHooks)
+    /// callbacks, which can consume the event first. Mutating the hook here does not trigger
+    /// [`changed_hook`](WindowHook::changed_hook).
+    fn on_window_event(
+        &mut self,
+        entity: Entity,
+        winit_window: &winit::window::Window,
+        event: &winit::event::WindowEvent,
+    ) {
+    }
+    /// Configures the root [`accesskit`](bevy_a11y::accesskit) node of a window, e.g. to change
+    /// its role or give it a description.
+    ///
+    /// The node is named after [`Window::title`] and has the [`Window`](bevy_a11y::accesskit::Role::Window) role before
+    /// this is called. This is only called once assistive technology requests the accessibility
+    /// tree of the window, which may be from another thread, with the hook as it was when the
+    /// window was created.
+    fn accessibility_hook(&self, entity: Entity, root: &mut NodeBuilder) {}
+    /// Called every update for each window with this hook, if [`WindowHook::WANTS_TICK`] is
+    /// `true`.
+    ///
+    /// This runs after changes to the hook were applied, regardless of whether anything changed.
+    /// Mutating the hook here does not trigger [`changed_hook`](WindowHook::changed_hook).
+    fn tick_hook(&mut self, entity: Entity, winit_window: &winit::window::Window) {}
 }
 
 /// Component that represents no hook. It should not be instanced.
 #[derive(Clone, Component, Debug, Default)]
 pub struct NoHook;
 
-impl WindowHook for NoHook {
-    fn builder_hook(&self, _: &Window, window_builder: WindowBuilder) -> WindowBuilder {
-        window_builder
+impl WindowHook for NoHook {}
+
+/// A [`Plugin`] that adds the systems propagating [`Window`] and [`WindowHook`] changes to the
+/// `winit` backend, in the order they need to run.
+///
+/// Only entities matching the filter `F` are updated.
+///
+/// This is added by [`HookedWinitPlugin`](crate::HookedWinitPlugin), and only needs to be added
+/// manually when replacing it. Windows themselves are created by the runner, since creation
+/// requires access to the event loop.
+pub struct WindowHookPlugin<T, F = ()> {
+    marker: PhantomData<fn() -> (T, F)>,
+}
+
+impl<T: WindowHook, F: QueryFilter + 'static> WindowHookPlugin<T, F> {
+    /// Creates a new [`WindowHookPlugin`].
+    pub fn new() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: WindowHook, F: QueryFilter + 'static> Default for WindowHookPlugin<T, F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: WindowHook, F: QueryFilter + 'static> Plugin for WindowHookPlugin<T, F> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Last,
+            (
+                applied_video_modes,
+                applied_cursor_changes,
+                redraw_on_window_changes::<F>,
+                // `exit_on_all_closed` only checks if windows exist but doesn't access data,
+                // so we don't need to care about its ordering relative to `changed_windows`
+                changed_windows::<F>
+                    .ambiguous_with(exit_on_all_closed)
+                    .run_if(not_batching),
+                changed_cursors::<F>.run_if(not_batching),
+                changed_window_extras::<F>.run_if(not_batching),
+                cursor_warps,
+                confine_cursors,
+                hit_test_masks,
+                changed_hooks::<T, F>.run_if(not_batching),
+                tick_hooks::<T, F>.run_if(wants_tick::<T>),
+                soft_keyboard_requests::<T>,
+                cursor_policy_requests::<T>,
+                fullscreen_changes::<T>,
+                changed_window_states,
+                changed_current_monitors,
+                winit_window_commands,
+                despawn_windows::<T>,
+                clear_invalidated.run_if(not_batching),
+            )
+                .chain(),
+        );
     }
 }
@@ -3,21 +3,35 @@ use bevy_a11y::{
     accesskit::{NodeBuilder, NodeClassSet, NodeId, Role, Tree, TreeUpdate},
     AccessibilityRequested,
 };
-use bevy_ecs::entity::Entity;
+use bevy_ecs::{entity::Entity, system::Resource};
+use std::sync::{Arc, Mutex, PoisonError};
 
-use bevy_ecs::entity::EntityHashMap;
-use bevy_utils::{tracing::warn, HashMap};
-use bevy_window::{CursorGrabMode, Window, WindowMode, WindowPosition, WindowResolution};
+use bevy_ecs::entity::{EntityHashMap, EntityHashSet};
+use bevy_math::{IRect, IVec2, Rect, UVec2, Vec2};
+use bevy_utils::{
+    tracing::{debug, warn},
+    Duration, HashMap, Instant,
+};
+use bevy_window::{
+    CursorGrabMode, MonitorSelection, Window, WindowMode, WindowPosition, WindowResizeConstraints,
+    WindowResolution,
+};
 
+use raw_window_handle::HasWindowHandle;
 use winit::{
-    dpi::{LogicalSize, PhysicalPosition},
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Size},
     monitor::MonitorHandle,
 };
 
 use crate::{
     accessibility::{AccessKitAdapters, WinitActionHandler, WinitActionHandlers},
-    converters::{convert_enabled_buttons, convert_window_level, convert_window_theme},
+    converters::{
+        convert_cursor_grab_mode, convert_cursor_icon, convert_enabled_buttons,
+        convert_window_icon, convert_window_level, convert_window_theme, from_physical_position,
+        from_physical_size, ime_area, to_logical_position, to_logical_size, to_physical_position,
+    },
     winit_hook::WindowHook,
+    PositionFallback, RequestScreenshot, WindowExtras, WindowState,
 };
 
 /// A resource mapping window entities to their `winit`-backend [`Window`](winit::window::Window)
@@ -30,6 +44,56 @@ pub struct WinitWindows {
     pub entity_to_winit: EntityHashMap<winit::window::WindowId>,
     /// Maps `winit` window identifiers to entities.
     pub winit_to_entity: HashMap<winit::window::WindowId, Entity>,
+    /// Window entities whose creation was deferred, with the reason it was last deferred for.
+    pub(crate) deferred: EntityHashMap<Deferral>,
+    /// Window entities that should have all their properties re-applied on the next update.
+    pub(crate) invalidated: EntityHashSet,
+    /// Whether change propagation is paused by [`WinitWindows::begin_batch`].
+    pub(crate) batching: bool,
+    /// Minimized window entities with geometry changes to apply once they are restored.
+    pub(crate) pending_geometry: EntityHashSet,
+    /// Window entities switched to an exclusive video mode with
+    /// [`WinitWindows::set_exclusive_video_mode`], whose [`Window::mode`] still needs to be synced.
+    pub(crate) video_mode_changes: EntityHashSet,
+    /// Video modes set with [`WinitWindows::set_exclusive_video_mode`], preferred over the best
+    /// video mode whenever [`WindowMode::Fullscreen`] is (re-)applied, until the window leaves it.
+    pub(crate) requested_video_modes: EntityHashMap<VideoModeInfo>,
+    /// The inner sizes of windows before they entered fullscreen, restored when they leave it,
+    /// since some platforms restore windows leaving fullscreen to a tiny size.
+    pub(crate) windowed_sizes: EntityHashMap<PhysicalSize<u32>>,
+    /// The cursor visibility last applied to each window entity.
+    pub(crate) cursor_visibility: EntityHashMap<bool>,
+    /// Window entities whose cursor visibility was set with [`WinitWindows::set_cursor_visible`],
+    /// whose [`Window::cursor`] still needs to be synced.
+    pub(crate) cursor_visibility_changes: EntityHashSet,
+    /// Cursor grab modes applied with [`WinitWindows::set_cursor_grab`], whose [`Window::cursor`]
+    /// still needs to be synced.
+    pub(crate) cursor_grab_changes: EntityHashMap<CursorGrabMode>,
+    /// Fullscreen transitions applied to the winit windows, forwarded to the window hooks.
+    pub(crate) fullscreen_changes: EntityHashMap<WindowMode>,
+    /// Pending on-screen keyboard visibility requests, forwarded to the window hooks.
+    pub(crate) soft_keyboard_requests: EntityHashMap<bool>,
+    /// Window entities that were created or had their cursor changed, forwarded to the window
+    /// hooks' [`WindowHook::cursor_policy_hook`].
+    pub(crate) cursor_policy_requests: EntityHashSet,
+    /// Pending screenshot requests, sent as [`RequestScreenshot`] events on the next update.
+    pub(crate) screenshot_requests: Vec<RequestScreenshot>,
+    /// Logical rectangles the cursor is confined to, by window entity.
+    pub(crate) cursor_confinements: EntityHashMap<Rect>,
+    /// The window icons last applied with [`WinitWindows::set_window_icon`], by window entity.
+    pub(crate) window_icons: EntityHashMap<WindowIcon>,
+    /// Logical regions clicks pass through, set with [`WinitWindows::set_hit_test_mask`].
+    pub(crate) hit_test_masks: EntityHashMap<Vec<Rect>>,
+    /// Window entities whose hit test is currently disabled because the cursor is over their
+    /// [hit test mask](WinitWindows::set_hit_test_mask).
+    pub(crate) hit_test_masked: EntityHashSet,
+    /// Window entities created with a [`QuietWindow`](crate::QuietWindow) marker.
+    pub(crate) quiet: EntityHashSet,
+    /// Aspect ratios (width / height) kept while resizing, set with
+    /// [`WinitWindows::set_aspect_ratio`].
+    pub(crate) aspect_ratios: EntityHashMap<f32>,
+    /// The number of failed creation attempts of window entities, with the time of the last one.
+    pub(crate) creation_attempts: EntityHashMap<(u32, Instant)>,
     // Many `winit` window functions (e.g. `set_window_icon`) can only be called on the main thread.
     // If they're called on other threads, the program might hang. This marker indicates that this
     // type is not thread-safe and will be `!Send` and `!Sync`.
@@ -38,16 +102,56 @@ pub struct WinitWindows {
 
 impl WinitWindows {
     /// Creates a `winit` window and associates it with our entity.
+    ///
+    /// If the requested cursor grab mode isn't supported, `window` is updated with the mode that
+    /// was applied instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `winit` fails to create the window. Use [`WinitWindows::try_create_window`] to
+    /// handle the error instead.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_window<T: WindowHook>(
         &mut self,
         event_loop: &winit::event_loop::EventLoopWindowTarget<()>,
         entity: Entity,
-        window: &Window,
+        window: &mut Window,
         hook: Option<&T>,
+        extras: Option<&WindowExtras>,
         adapters: &mut AccessKitAdapters,
         handlers: &mut WinitActionHandlers,
         accessibility_requested: &AccessibilityRequested,
     ) -> &winit::window::Window {
+        self.try_create_window(
+            event_loop,
+            entity,
+            window,
+            hook,
+            extras,
+            adapters,
+            handlers,
+            accessibility_requested,
+        )
+        .expect("Failed to create window")
+    }
+
+    /// Creates a `winit` window and associates it with our entity, or returns the error `winit`
+    /// failed with.
+    ///
+    /// Like [`WinitWindows::create_window`], but if creation fails, `window` is left as it was so
+    /// creation can be attempted again.
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_create_window<T: WindowHook>(
+        &mut self,
+        event_loop: &winit::event_loop::EventLoopWindowTarget<()>,
+        entity: Entity,
+        window: &mut Window,
+        hook: Option<&T>,
+        extras: Option<&WindowExtras>,
+        adapters: &mut AccessKitAdapters,
+        handlers: &mut WinitActionHandlers,
+        accessibility_requested: &AccessibilityRequested,
+    ) -> Result<&winit::window::Window, winit::error::OsError> {
         let mut winit_window_builder = winit::window::WindowBuilder::new();
 
         // Due to a UIA limitation, winit windows need to be invisible for the
@@ -70,8 +174,16 @@ impl WinitWindows {
                         _ => unreachable!(),
                     };
 
-                    winit_window_builder
-                        .with_fullscreen(Some(winit::window::Fullscreen::Exclusive(videomode)))
+                    window.mode = exclusive_fullscreen_fallback(mode, videomode.is_some());
+                    if let Some(videomode) = videomode {
+                        winit_window_builder
+                            .with_fullscreen(Some(winit::window::Fullscreen::Exclusive(videomode)))
+                    } else {
+                        warn!("Primary monitor reports no video modes, falling back to borderless fullscreen for window {:?}", window.title);
+                        winit_window_builder.with_fullscreen(Some(
+                            winit::window::Fullscreen::Borderless(Some(primary_monitor)),
+                        ))
+                    }
                 } else {
                     warn!("Could not determine primary monitor, ignoring exclusive fullscreen request for window {:?}", window.title);
                     winit_window_builder
@@ -106,6 +218,15 @@ impl WinitWindows {
             .with_transparent(window.transparent)
             .with_visible(window.visible);
 
+        // Apply a pending maximize request up front, so restored windows don't appear at their
+        // windowed size first. Position and size are already applied above, and the cache is
+        // seeded from the created window, so nothing is corrected after creation.
+        let maximize_request = window.internal.take_maximize_request();
+        let state = extras.and_then(|extras| extras.state);
+        if let Some(maximized) = initially_maximized(maximize_request, state) {
+            winit_window_builder = winit_window_builder.with_maximized(maximized);
+        }
+
         #[cfg(any(
             target_os = "linux",
             target_os = "dragonfly",
@@ -160,28 +281,32 @@ impl WinitWindows {
             }
         }
 
-        let constraints = window.resize_constraints.check_constraints();
-        let min_inner_size = LogicalSize {
-            width: constraints.min_width,
-            height: constraints.min_height,
-        };
-        let max_inner_size = LogicalSize {
-            width: constraints.max_width,
-            height: constraints.max_height,
-        };
+        let physical_constraints = extras.is_some_and(|extras| extras.physical_resize_constraints);
+        warn_invalid_constraints(&window.resize_constraints);
+        let (min_inner_size, max_inner_size) =
+            inner_size_limits(&window.resize_constraints, physical_constraints);
 
-        let winit_window_builder =
-            if constraints.max_width.is_finite() && constraints.max_height.is_finite() {
-                winit_window_builder
-                    .with_min_inner_size(min_inner_size)
-                    .with_max_inner_size(max_inner_size)
-            } else {
-                winit_window_builder.with_min_inner_size(min_inner_size)
-            };
+        let winit_window_builder = if let Some(max_inner_size) = max_inner_size {
+            winit_window_builder
+                .with_min_inner_size(min_inner_size)
+                .with_max_inner_size(max_inner_size)
+        } else {
+            winit_window_builder.with_min_inner_size(min_inner_size)
+        };
 
-        #[allow(unused_mut)]
         let mut winit_window_builder = winit_window_builder.with_title(window.title.as_str());
 
+        if let Some(increments) = extras.and_then(|extras| extras.resize_increments) {
+            winit_window_builder =
+                winit_window_builder.with_resize_increments(to_logical_size(increments));
+        }
+
+        // Like transparency, blur has to be requested before the surface is created to work
+        // reliably.
+        if extras.is_some_and(|extras| extras.blur) {
+            winit_window_builder = winit_window_builder.with_blur(true);
+        }
+
         #[cfg(target_arch = "wasm32")]
         {
             use wasm_bindgen::JsCast;
@@ -206,24 +331,56 @@ impl WinitWindows {
             winit_window_builder = winit_window_builder.with_append(true);
         }
 
+        if let Some(parent) = extras.and_then(|extras| extras.parent) {
+            match self.get_window(parent).map(|parent| parent.window_handle()) {
+                Some(Ok(parent_handle)) => {
+                    // SAFETY: the handle comes from a live window owned by `self`.
+                    winit_window_builder = unsafe {
+                        winit_window_builder.with_parent_window(Some(parent_handle.as_raw()))
+                    };
+                }
+                Some(Err(err)) => {
+                    warn!("Could not get the handle of parent window {parent:?}: {err}");
+                }
+                None => {
+                    warn!("Parent window {parent:?} doesn't exist, creating a top-level window")
+                }
+            }
+        }
+
         if let Some(hook) = hook {
-            winit_window_builder = hook.builder_hook(&window, winit_window_builder);
+            winit_window_builder = hook.attributes_hook(window, winit_window_builder);
         }
 
-        let winit_window = winit_window_builder.build(event_loop).unwrap();
+        let winit_window = match winit_window_builder.build(event_loop) {
+            Ok(winit_window) => winit_window,
+            Err(err) => {
+                // Keep the request for the next attempt.
+                if let Some(maximized) = maximize_request {
+                    window.set_maximized(maximized);
+                }
+                return Err(err);
+            }
+        };
         let name = window.title.clone();
 
-        let mut root_builder = NodeBuilder::new(Role::Window);
-        root_builder.set_name(name.into_boxed_str());
-        let root = root_builder.build(&mut NodeClassSet::lock_global());
-
         let accesskit_window_id = NodeId(entity.to_bits());
         let handler = WinitActionHandler::default();
         let accessibility_requested = accessibility_requested.clone();
+        let root_hook = hook.cloned();
         let adapter = Adapter::with_action_handler(
             &winit_window,
             move || {
                 accessibility_requested.set(true);
+
+                // The root node is only built once assistive technology asks for the tree.
+                let mut root_builder = NodeBuilder::new(Role::Window);
+                root_builder.set_name(name.into_boxed_str());
+                if let Some(hook) = root_hook {
+                    hook.accessibility_hook(entity, &mut root_builder);
+                }
+                let root = root_builder.build(&mut NodeClassSet::lock_global());
+
                 TreeUpdate {
                     nodes: vec![(accesskit_window_id, root)],
                     tree: Some(Tree::new(accesskit_window_id)),
@@ -237,10 +394,18 @@ impl WinitWindows {
 
         // Do not set the grab mode on window creation if it's none. It can fail on mobile.
         if window.cursor.grab_mode != CursorGrabMode::None {
-            attempt_grab(&winit_window, window.cursor.grab_mode);
+            window.cursor.grab_mode = attempt_grab(&winit_window, window.cursor.grab_mode);
+            // Some platforms reset the cursor icon when grabbing it.
+            winit_window.set_cursor_icon(convert_cursor_icon(window.cursor.icon));
+        }
+
+        // Windows can't be built minimized.
+        if state == Some(WindowState::Minimized) {
+            apply_window_state(&winit_window, WindowState::Minimized);
         }
 
         winit_window.set_cursor_visible(window.cursor.visible);
+        self.cursor_visibility.insert(entity, window.cursor.visible);
 
         // Do not set the cursor hittest on window creation if it's false, as it will always fail on
         // some platforms and log an unfixable warning.
@@ -251,6 +416,7 @@ impl WinitWindows {
                     window.title, err
                 );
             }
+            check_hit_test_passthrough(window);
         }
 
         if let Some(hook) = hook {
@@ -259,11 +425,15 @@ impl WinitWindows {
 
         self.entity_to_winit.insert(entity, winit_window.id());
         self.winit_to_entity.insert(winit_window.id(), entity);
+        self.cursor_policy_requests.insert(entity);
+
+        self.creation_attempts.remove(&entity);
 
-        self.windows
+        Ok(self
+            .windows
             .entry(winit_window.id())
             .insert(winit_window)
-            .into_mut()
+            .into_mut())
     }
 
     /// Get the winit window that is associated with our entity.
@@ -280,149 +450,1819 @@ impl WinitWindows {
         self.winit_to_entity.get(&winit_id).cloned()
     }
 
-    /// Remove a window from winit.
+    /// Get the current scale factor of the winit window associated with our entity.
     ///
-    /// This should mostly just be called when the window is closing.
-    pub fn remove_window(&mut self, entity: Entity) -> Option<winit::window::Window> {
-        let winit_id = self.entity_to_winit.remove(&entity)?;
-        // Don't remove from `winit_to_window_id` so we know the window used to exist.
-        self.windows.remove(&winit_id)
+    /// Unlike [`Window::scale_factor`], this is read directly from winit and never lags behind.
+    pub fn scale_factor(&self, entity: Entity) -> Option<f64> {
+        self.get_window(entity).map(|window| window.scale_factor())
     }
-}
 
-/// Gets the "best" video mode which fits the given dimensions.
-///
-/// The heuristic for "best" prioritizes width, height, and refresh rate in that order.
-pub fn get_fitting_videomode(
-    monitor: &MonitorHandle,
-    width: u32,
-    height: u32,
-) -> winit::monitor::VideoMode {
-    let mut modes = monitor.video_modes().collect::<Vec<_>>();
+    /// Get the current physical size of the client area of the winit window associated with
+    /// our entity.
+    pub fn inner_size(&self, entity: Entity) -> Option<PhysicalSize<u32>> {
+        self.get_window(entity).map(|window| window.inner_size())
+    }
 
-    fn abs_diff(a: u32, b: u32) -> u32 {
-        if a > b {
-            return a - b;
-        }
-        b - a
+    /// Get the current physical size of the winit window associated with our entity, including
+    /// the title bar and borders.
+    pub fn outer_size(&self, entity: Entity) -> Option<PhysicalSize<u32>> {
+        self.get_window(entity).map(|window| window.outer_size())
     }
 
-    modes.sort_by(|a, b| {
-        use std::cmp::Ordering::*;
-        match abs_diff(a.size().width, width).cmp(&abs_diff(b.size().width, width)) {
-            Equal => {
-                match abs_diff(a.size().height, height).cmp(&abs_diff(b.size().height, height)) {
-                    Equal => b
-                        .refresh_rate_millihertz()
-                        .cmp(&a.refresh_rate_millihertz()),
-                    default => default,
-                }
+    /// Get the current physical position of the top-left of the winit window associated with our
+    /// entity, including the title bar and borders.
+    ///
+    /// Returns `None` if the entity has no winit window, or if the platform can't report the
+    /// position (e.g. Wayland).
+    pub fn outer_position(&self, entity: Entity) -> Option<IVec2> {
+        let position = self.get_window(entity)?.outer_position().ok()?;
+        Some(from_physical_position(position))
+    }
+
+    /// Get the current physical position of the top-left of the client area of the winit window
+    /// associated with our entity, excluding the title bar and borders.
+    ///
+    /// This is where the rendered content starts, while [`WinitWindows::outer_position`] and
+    /// [`Window::position`] refer to the window frame.
+    ///
+    /// Returns `None` if the entity has no winit window, or if the platform can't report the
+    /// position (e.g. Wayland, Android and the web).
+    pub fn inner_position(&self, entity: Entity) -> Option<IVec2> {
+        let position = self.get_window(entity)?.inner_position().ok()?;
+        Some(from_physical_position(position))
+    }
+
+    /// Moves the winit window associated with our entity so that the top-left of its client area
+    /// ends up at a physical position.
+    ///
+    /// `winit` can only move the window frame, so this offsets `position` by the current size of
+    /// the title bar and borders. [`Window::position`] is updated once winit reports the move.
+    ///
+    /// Returns `false` if the entity has no winit window, or if the platform can't report the
+    /// inner and outer positions.
+    pub fn set_inner_position(&self, entity: Entity, position: IVec2) -> bool {
+        let (Some(inner), Some(outer)) = (self.inner_position(entity), self.outer_position(entity))
+        else {
+            return false;
+        };
+        self.set_outer_position(entity, position - (inner - outer))
+    }
+
+    /// Moves the top-left of the winit window associated with our entity to a physical position,
+    /// including the title bar and borders.
+    ///
+    /// [`Window::position`] is updated once winit reports the move.
+    ///
+    /// Returns `false` if the entity has no winit window.
+    pub fn set_outer_position(&self, entity: Entity, position: IVec2) -> bool {
+        self.get_window(entity)
+            .map(|window| window.set_outer_position(to_physical_position(position)))
+            .is_some()
+    }
+
+    /// When the next failed window creation is retried, if any are left to retry.
+    pub(crate) fn next_creation_retry(&self, retries: u32, delay: Duration) -> Option<Instant> {
+        self.creation_attempts
+            .values()
+            .filter(|&&(attempts, _)| attempts <= retries)
+            .map(|&(attempts, last_failure)| last_failure + creation_retry_delay(delay, attempts))
+            .min()
+    }
+
+    /// Whether a winit window was created for our entity.
+    pub fn is_realized(&self, entity: Entity) -> bool {
+        self.entity_to_winit.contains_key(&entity)
+    }
+
+    /// The window entities whose creation was deferred, e.g. because their
+    /// [`WindowHook::ready`] returned `false` or their parent window doesn't exist yet.
+    ///
+    /// Entities are listed in no particular order, from the update their creation was first
+    /// deferred until their window is created or removed. Windows waiting to retry a failed
+    /// creation, or not attempted yet, aren't listed.
+    pub fn deferred_windows(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.deferred.keys().copied()
+    }
+
+    /// Moves the cursor to `position`, in physical pixels relative to the top-left of the desktop.
+    ///
+    /// [`Window::set_physical_cursor_position`] and [`CursorWarp`](crate::CursorWarp) take
+    /// positions relative to the client area of the window instead. This converts `position`
+    /// using the current position of the client area, so the cursor can be moved to a point
+    /// outside of the window, e.g. to hand it over to another window. Whether the cursor can
+    /// leave the window is up to the platform.
+    ///
+    /// Returns `false` if the entity has no winit window, or the platform can't report the window
+    /// position or move the cursor.
+    pub fn set_cursor_position_global(&self, entity: Entity, position: IVec2) -> bool {
+        let Some(winit_window) = self.get_window(entity) else {
+            return false;
+        };
+        let Ok(client_position) = winit_window.inner_position() else {
+            return false;
+        };
+        let relative = position - from_physical_position(client_position);
+        match winit_window.set_cursor_position(to_physical_position(relative)) {
+            Ok(()) => true,
+            Err(err) => {
+                warn!("Could not move the cursor of window {entity:?}: {err}");
+                false
             }
-            default => default,
         }
-    });
+    }
 
-    modes.first().unwrap().clone()
-}
+    /// Brings the winit window associated with our entity to the front and gives it input focus.
+    ///
+    /// Returns `false` if the entity has no winit window. The
+    /// [`Window::focused`] field is updated once winit reports the focus change.
+    pub fn focus_window(&self, entity: Entity) -> bool {
+        self.get_window(entity)
+            .map(|window| window.focus_window())
+            .is_some()
+    }
 
-/// Gets the "best" videomode from a monitor.
-///
-/// The heuristic for "best" prioritizes width, height, and refresh rate in that order.
-pub fn get_best_videomode(monitor: &MonitorHandle) -> winit::monitor::VideoMode {
-    let mut modes = monitor.video_modes().collect::<Vec<_>>();
-    modes.sort_by(|a, b| {
-        use std::cmp::Ordering::*;
-        match b.size().width.cmp(&a.size().width) {
-            Equal => match b.size().height.cmp(&a.size().height) {
-                Equal => b
-                    .refresh_rate_millihertz()
-                    .cmp(&a.refresh_rate_millihertz()),
-                default => default,
-            },
-            default => default,
-        }
-    });
+    /// Whether the winit window associated with our entity currently has input focus.
+    pub fn is_focused(&self, entity: Entity) -> Option<bool> {
+        self.get_window(entity).map(|window| window.has_focus())
+    }
 
-    modes.first().unwrap().clone()
-}
+    /// Maximizes or unmaximizes the winit window associated with our entity right away.
+    ///
+    /// Unlike [`Window::set_maximized`], this doesn't wait for the next update. The
+    /// [`WinitWindowState`](crate::WinitWindowState) is updated, and a
+    /// [`WindowMaximized`](crate::WindowMaximized) event sent, once the change is read back.
+    ///
+    /// Returns `false` if the entity has no winit window.
+    pub fn set_maximized(&self, entity: Entity, maximized: bool) -> bool {
+        self.get_window(entity)
+            .map(|window| window.set_maximized(maximized))
+            .is_some()
+    }
 
-pub(crate) fn attempt_grab(winit_window: &winit::window::Window, grab_mode: CursorGrabMode) {
-    let grab_result = match grab_mode {
-        CursorGrabMode::None => winit_window.set_cursor_grab(winit::window::CursorGrabMode::None),
-        CursorGrabMode::Confined => winit_window
-            .set_cursor_grab(winit::window::CursorGrabMode::Confined)
-            .or_else(|_e| winit_window.set_cursor_grab(winit::window::CursorGrabMode::Locked)),
-        CursorGrabMode::Locked => winit_window
-            .set_cursor_grab(winit::window::CursorGrabMode::Locked)
-            .or_else(|_e| winit_window.set_cursor_grab(winit::window::CursorGrabMode::Confined)),
-    };
+    /// Whether the winit window associated with our entity is currently maximized.
+    pub fn is_maximized(&self, entity: Entity) -> Option<bool> {
+        self.get_window(entity).map(|window| window.is_maximized())
+    }
 
-    if let Err(err) = grab_result {
-        let err_desc = match grab_mode {
-            CursorGrabMode::Confined | CursorGrabMode::Locked => "grab",
-            CursorGrabMode::None => "ungrab",
+    /// Keeps the client area of the window associated with our entity at an aspect ratio of
+    /// `ratio` (width / height) while it's resized, or stops doing so with `None`.
+    ///
+    /// `winit` can't enforce an aspect ratio, so resizes are corrected after the fact: when the
+    /// window is resized, the dimension that changed the least is adjusted to the ratio, and the
+    /// corrected size is requested. The current size is corrected right away.
+    ///
+    /// Returns `false` if the entity has no winit window, or if `ratio` isn't positive and finite.
+    pub fn set_aspect_ratio(&mut self, entity: Entity, ratio: Option<f32>) -> bool {
+        let Some(winit_window) = self.get_window(entity) else {
+            return false;
         };
+        let Some(ratio) = ratio else {
+            self.aspect_ratios.remove(&entity);
+            return true;
+        };
+        if !ratio.is_finite() || ratio <= 0. {
+            warn!("Ignoring invalid aspect ratio {ratio} for window {entity:?}");
+            return false;
+        }
+        let size = winit_window.inner_size();
+        let fitted = fit_aspect_ratio(size, size, ratio);
+        if fitted != size {
+            let _ = winit_window.request_inner_size(fitted);
+        }
+        self.aspect_ratios.insert(entity, ratio);
+        true
+    }
 
-        bevy_utils::tracing::error!("Unable to {} cursor: {}", err_desc, err);
+    /// The aspect ratio (width / height) the window associated with our entity is kept at, set
+    /// with [`WinitWindows::set_aspect_ratio`].
+    pub fn aspect_ratio(&self, entity: Entity) -> Option<f32> {
+        self.aspect_ratios.get(&entity).copied()
     }
-}
 
-/// Compute the physical window position for a given [`WindowPosition`].
-// Ideally we could generify this across window backends, but we only really have winit atm
-// so whatever.
-pub fn winit_window_position(
-    position: &WindowPosition,
-    resolution: &WindowResolution,
-    mut available_monitors: impl Iterator<Item = MonitorHandle>,
-    primary_monitor: Option<MonitorHandle>,
-    current_monitor: Option<MonitorHandle>,
-) -> Option<PhysicalPosition<i32>> {
-    match position {
-        WindowPosition::Automatic => {
-            /* Window manager will handle position */
+    /// Captures the current state of the winit window associated with our entity, to restore it
+    /// later with [`WindowSnapshot::apply`].
+    ///
+    /// Returns `None` if the entity has no winit window.
+    pub fn snapshot(&self, entity: Entity) -> Option<WindowSnapshot> {
+        let winit_window = self.get_window(entity)?;
+        let mode = match winit_window.fullscreen() {
+            Some(winit::window::Fullscreen::Borderless(_)) => WindowMode::BorderlessFullscreen,
+            Some(winit::window::Fullscreen::Exclusive(_)) => WindowMode::Fullscreen,
+            None => WindowMode::Windowed,
+        };
+        Some(WindowSnapshot {
+            position: winit_window
+                .outer_position()
+                .ok()
+                .map(from_physical_position),
+            size: from_physical_size(winit_window.inner_size()),
+            scale_factor: winit_window.scale_factor(),
+            maximized: winit_window.is_maximized(),
+            monitor_name: winit_window
+                .current_monitor()
+                .and_then(|monitor| monitor.name()),
+            mode,
+            visible: winit_window.is_visible().unwrap_or(true),
+        })
+    }
+
+    /// Minimizes or restores the winit window associated with our entity right away.
+    ///
+    /// Unlike [`Window::set_minimized`], this doesn't wait for the next update. The
+    /// [`WinitWindowState`](crate::WinitWindowState) is updated, and a
+    /// [`WindowMinimized`](crate::WindowMinimized) event sent, once the change is read back.
+    ///
+    /// Returns `false` if the entity has no winit window.
+    pub fn set_minimized(&self, entity: Entity, minimized: bool) -> bool {
+        self.get_window(entity)
+            .map(|window| window.set_minimized(minimized))
+            .is_some()
+    }
+
+    /// Whether the winit window associated with our entity is currently minimized.
+    ///
+    /// Returns `None` if the entity has no winit window, or the platform can't tell.
+    pub fn is_minimized(&self, entity: Entity) -> Option<bool> {
+        self.get_window(entity)
+            .and_then(|window| window.is_minimized())
+    }
+
+    /// Selects the native tab following the window associated with our entity in its tab group.
+    ///
+    /// Windows are grouped into tabs by their
+    /// [tabbing identifier](crate::platform::tabbing_identifier).
+    ///
+    /// Returns `false` if the entity has no winit window.
+    ///
+    /// ## Platform-specific
+    ///
+    /// Only supported on macOS, always returns `false` elsewhere.
+    pub fn select_next_tab(&self, entity: Entity) -> bool {
+        #[cfg(target_os = "macos")]
+        {
+            use winit::platform::macos::WindowExtMacOS;
+            self.get_window(entity)
+                .map(|window| window.select_next_tab())
+                .is_some()
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            let _ = entity;
+            false
+        }
+    }
+
+    /// Selects the native tab preceding the window associated with our entity in its tab group.
+    ///
+    /// Returns `false` if the entity has no winit window.
+    ///
+    /// ## Platform-specific
+    ///
+    /// Only supported on macOS, always returns `false` elsewhere.
+    pub fn select_previous_tab(&self, entity: Entity) -> bool {
+        #[cfg(target_os = "macos")]
+        {
+            use winit::platform::macos::WindowExtMacOS;
+            self.get_window(entity)
+                .map(|window| window.select_previous_tab())
+                .is_some()
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            let _ = entity;
+            false
+        }
+    }
+
+    /// The number of native tabs in the tab group of the window associated with our entity.
+    ///
+    /// ## Platform-specific
+    ///
+    /// Only supported on macOS, always returns `None` elsewhere.
+    pub fn num_tabs(&self, entity: Entity) -> Option<usize> {
+        #[cfg(target_os = "macos")]
+        {
+            use winit::platform::macos::WindowExtMacOS;
+            self.get_window(entity).map(|window| window.num_tabs())
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            let _ = entity;
             None
         }
-        WindowPosition::Centered(monitor_selection) => {
-            use bevy_window::MonitorSelection::*;
-            let maybe_monitor = match monitor_selection {
-                Current => {
-                    if current_monitor.is_none() {
-                        warn!("Can't select current monitor on window creation or cannot find current monitor!");
-                    }
-                    current_monitor
-                }
-                Primary => primary_monitor,
-                Index(n) => available_monitors.nth(*n),
-            };
+    }
 
-            if let Some(monitor) = maybe_monitor {
-                let screen_size = monitor.size();
+    /// Forgets which properties of the window associated with our entity were already applied,
+    /// so that all of them are pushed to winit again on the next update.
+    ///
+    /// This is useful to recover after the OS reset the window's state, e.g. after a display
+    /// reconfiguration. The window's hook gets [`WindowHook::window_hook`] called again. Returns
+    /// `false` if the entity has no winit window.
+    pub fn invalidate_cache(&mut self, entity: Entity) -> bool {
+        if self.get_window(entity).is_none() {
+            return false;
+        }
+        self.invalidated.insert(entity);
+        true
+    }
 
-                // We use the monitors scale factor here since `WindowResolution.scale_factor` is
-                // not yet populated when windows are created during plugin setup.
-                let scale_factor = monitor.scale_factor();
+    /// Pauses propagating changes to [`Window`]s, [`WindowExtras`] and window hooks to winit until
+    /// [`WinitWindows::end_batch`] is called.
+    ///
+    /// Changes made in between are not lost: the propagating systems don't run while batching, so
+    /// change detection still sees every change since they last ran, and only the final state of
+    /// each window is applied on the first update after the batch ends. Window creation,
+    /// destruction, and the imperative methods of [`WinitWindows`] are not paused.
+    pub fn begin_batch(&mut self) {
+        self.batching = true;
+    }
 
-                // Logical to physical window size
-                let (width, height): (u32, u32) =
-                    LogicalSize::new(resolution.width(), resolution.height())
-                        .to_physical::<u32>(scale_factor)
-                        .into();
+    /// Resumes propagating changes after [`WinitWindows::begin_batch`].
+    pub fn end_batch(&mut self) {
+        self.batching = false;
+    }
 
-                let position = PhysicalPosition {
-                    x: screen_size.width.saturating_sub(width) as f64 / 2.
-                        + monitor.position().x as f64,
-                    y: screen_size.height.saturating_sub(height) as f64 / 2.
-                        + monitor.position().y as f64,
-                };
+    /// Whether change propagation is paused by [`WinitWindows::begin_batch`].
+    pub fn is_batching(&self) -> bool {
+        self.batching
+    }
 
-                Some(position.cast::<i32>())
-            } else {
-                warn!("Couldn't get monitor selected with: {monitor_selection:?}");
-                None
-            }
+    /// Shows or hides the on-screen keyboard for the window associated with our entity.
+    ///
+    /// This allows IME input for the window, and forwards the request to
+    /// [`WindowHook::request_soft_keyboard_hook`] on the next update.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / X11 / Wayland:** IME is allowed or disallowed. Whether a soft keyboard
+    ///   appears is up to the OS.
+    /// - **iOS / Android / Web:** `winit` doesn't support this, so it's entirely up to the hook.
+    ///
+    /// Returns `false` if the entity has no winit window.
+    pub fn request_soft_keyboard(&mut self, entity: Entity, visible: bool) -> bool {
+        let Some(winit_window) = self.get_window(entity) else {
+            return false;
+        };
+        winit_window.set_ime_allowed(visible);
+        self.soft_keyboard_requests.insert(entity, visible);
+        true
+    }
+
+    /// Requests a capture of the client area of the window associated with our entity.
+    ///
+    /// `winit` can't read back pixels, so this only sends a [`RequestScreenshot`] event on the next
+    /// update, for the renderer to act on. The event carries the physical size and scale factor of
+    /// the winit window at the time of the request, so the capture is dimensioned correctly even if
+    /// the window is resized in the meantime.
+    ///
+    /// Returns the request, or `None` if the entity has no winit window.
+    pub fn request_screenshot(&mut self, entity: Entity) -> Option<RequestScreenshot> {
+        let winit_window = self.get_window(entity)?;
+        let size = winit_window.inner_size();
+        let request = RequestScreenshot {
+            window: entity,
+            physical_width: size.width,
+            physical_height: size.height,
+            scale_factor: winit_window.scale_factor(),
+        };
+        self.screenshot_requests.push(request);
+        Some(request)
+    }
+
+    /// Lists the video modes of the monitor the window associated with our entity is on, best
+    /// first.
+    ///
+    /// Returns an empty list if the entity has no winit window or its monitor is unknown.
+    pub fn video_modes(&self, entity: Entity) -> Vec<VideoModeInfo> {
+        let Some(monitor) = self
+            .get_window(entity)
+            .and_then(|winit_window| winit_window.current_monitor())
+        else {
+            return Vec::new();
+        };
+        let mut modes = monitor.video_modes().collect::<Vec<_>>();
+        modes.sort_by(compare_videomodes);
+        modes.iter().map(VideoModeInfo::from).collect()
+    }
+
+    /// Switches the window associated with our entity to exclusive fullscreen with exactly the
+    /// given video mode of its current monitor, as listed by [`WinitWindows::video_modes`].
+    ///
+    /// [`Window::mode`] is set to [`WindowMode::Fullscreen`] on the next update. The video mode is
+    /// kept for as long as the window stays in that mode, so re-applying it (e.g. after
+    /// [`WinitWindows::invalidate_cache`]) doesn't switch to the best video mode instead.
+    ///
+    /// Returns `false` if the entity has no winit window, or if its monitor doesn't have the mode.
+    pub fn set_exclusive_video_mode(&mut self, entity: Entity, mode: VideoModeInfo) -> bool {
+        let Some(winit_window) = self.get_window(entity) else {
+            return false;
+        };
+        let Some(videomode) = winit_window
+            .current_monitor()
+            .and_then(|monitor| find_videomode(&monitor, mode))
+        else {
+            return false;
+        };
+        let windowed_size = winit_window
+            .fullscreen()
+            .is_none()
+            .then(|| winit_window.inner_size());
+        winit_window.set_fullscreen(Some(winit::window::Fullscreen::Exclusive(videomode)));
+        if let Some(size) = windowed_size {
+            self.windowed_sizes.insert(entity, size);
         }
-        WindowPosition::At(position) => {
-            Some(PhysicalPosition::new(position[0] as f64, position[1] as f64).cast::<i32>())
+        self.requested_video_modes.insert(entity, mode);
+        self.video_mode_changes.insert(entity);
+        self.fullscreen_changes
+            .insert(entity, WindowMode::Fullscreen);
+        true
+    }
+
+    /// The refresh rate the window associated with our entity is presented at, in millihertz.
+    ///
+    /// This is the refresh rate of the exclusive video mode while the window is in exclusive
+    /// fullscreen, and that of its current monitor otherwise. Returns `None` if the entity has no
+    /// winit window or the refresh rate is unknown.
+    pub fn current_refresh_rate(&self, entity: Entity) -> Option<u32> {
+        let winit_window = self.get_window(entity)?;
+        match exclusive_videomode(winit_window) {
+            Some(videomode) => Some(videomode.refresh_rate_millihertz),
+            None => winit_window
+                .current_monitor()
+                .and_then(|monitor| monitor.refresh_rate_millihertz()),
         }
     }
+
+    /// Switches the window associated with our entity, while in exclusive fullscreen, to the video
+    /// mode with the same resolution and the refresh rate closest to `millihertz`.
+    ///
+    /// [`ExclusiveVideoMode`](crate::ExclusiveVideoMode) is updated on the next update.
+    ///
+    /// Returns `false` if the entity has no winit window or isn't in exclusive fullscreen.
+    pub fn set_refresh_rate(&mut self, entity: Entity, millihertz: u32) -> bool {
+        let Some(winit_window) = self.get_window(entity) else {
+            return false;
+        };
+        let Some(current) = exclusive_videomode(winit_window) else {
+            return false;
+        };
+        let Some(mode) = self
+            .video_modes(entity)
+            .into_iter()
+            .filter(|mode| mode.size == current.size)
+            // The modes are sorted best first, so ties keep the higher bit depth.
+            .min_by_key(|mode| mode.refresh_rate_millihertz.abs_diff(millihertz))
+        else {
+            return false;
+        };
+        mode == current || self.set_exclusive_video_mode(entity, mode)
+    }
+
+    /// Shows or hides the cursor while it's over the window associated with our entity.
+    ///
+    /// The change is applied right away, and [`Window::cursor`] is updated to match on the next
+    /// update without the change being applied again.
+    ///
+    /// Returns `false` if the entity has no winit window.
+    pub fn set_cursor_visible(&mut self, entity: Entity, visible: bool) -> bool {
+        let Some(winit_window) = self.get_window(entity) else {
+            return false;
+        };
+        winit_window.set_cursor_visible(visible);
+        self.cursor_visibility.insert(entity, visible);
+        self.cursor_visibility_changes.insert(entity);
+        true
+    }
+
+    /// Grabs the cursor of the window associated with our entity, or releases it with
+    /// [`CursorGrabMode::None`].
+    ///
+    /// Unsupported modes fall back like [`Window::cursor`] changes do: from
+    /// [`CursorGrabMode::Locked`] to [`CursorGrabMode::Confined`] to [`CursorGrabMode::None`]. The
+    /// change is applied right away, and [`Window::cursor`] is updated to match on the next update
+    /// without the change being applied again.
+    ///
+    /// Returns the mode that was applied, or `None` if the entity has no winit window.
+    pub fn set_cursor_grab(
+        &mut self,
+        entity: Entity,
+        grab_mode: CursorGrabMode,
+    ) -> Option<CursorGrabMode> {
+        let applied = attempt_grab(self.get_window(entity)?, grab_mode);
+        self.cursor_grab_changes.insert(entity, applied);
+        Some(applied)
+    }
+
+    /// Whether the cursor is visible while it's over the window associated with our entity, as
+    /// last applied to the winit window.
+    ///
+    /// Returns `None` if the entity has no winit window.
+    pub fn is_cursor_visible(&self, entity: Entity) -> Option<bool> {
+        self.get_window(entity)?;
+        self.cursor_visibility.get(&entity).copied()
+    }
+
+    /// Sets the area of the window associated with our entity that the IME candidate box should
+    /// avoid, e.g. the caret of a text field.
+    ///
+    /// `position` is the top-left of the area and `size` its extent, both in logical pixels
+    /// relative to the top-left of the client area. Changing [`Window::ime_position`] afterwards
+    /// replaces the area with a default-sized one at the new position. For a text caret, use
+    /// [`WinitWindows::set_ime_caret`] instead.
+    ///
+    /// Returns `false` if the entity has no winit window.
+    pub fn set_ime_cursor_area(&self, entity: Entity, position: Vec2, size: Vec2) -> bool {
+        let Some(winit_window) = self.get_window(entity) else {
+            return false;
+        };
+        winit_window.set_ime_cursor_area(
+            to_logical_position(position),
+            to_logical_size(size).to_physical::<u32>(winit_window.scale_factor()),
+        );
+        true
+    }
+
+    /// Sets the text caret of the window associated with our entity, which the IME candidate box
+    /// should avoid.
+    ///
+    /// `caret_position` is the top of the caret and `caret_height` its height, both in logical
+    /// pixels relative to the top-left of the client area. See [`ime_area`] for how this is
+    /// converted for `winit`.
+    ///
+    /// Returns `false` if the entity has no winit window.
+    pub fn set_ime_caret(&self, entity: Entity, caret_position: Vec2, caret_height: f32) -> bool {
+        let Some(winit_window) = self.get_window(entity) else {
+            return false;
+        };
+        let (position, size) = ime_area(caret_position, caret_height, winit_window.scale_factor());
+        winit_window.set_ime_cursor_area(position, size);
+        true
+    }
+
+    /// Confines the cursor to a rectangle of the window associated with our entity, given in
+    /// logical pixels relative to the top-left of the client area.
+    ///
+    /// `winit` can only confine the cursor to the whole window, so this is done in software: the
+    /// cursor is moved back into the rectangle every update it is found outside of it. Confinement
+    /// is skipped while the cursor is [locked](CursorGrabMode::Locked) or the window lets clicks
+    /// pass through it. Since the cursor position is only known while it's over the window,
+    /// combine this with [`CursorGrabMode::Confined`] to keep the cursor from leaving the window
+    /// between updates.
+    ///
+    /// Returns `false` if the entity has no winit window.
+    pub fn confine_cursor_to_rect(&mut self, entity: Entity, rect: Rect) -> bool {
+        if self.get_window(entity).is_none() {
+            return false;
+        }
+        self.cursor_confinements.insert(entity, rect);
+        true
+    }
+
+    /// Stops confining the cursor of the window associated with our entity to a rectangle.
+    ///
+    /// Returns `false` if the cursor wasn't confined.
+    pub fn unconfine_cursor(&mut self, entity: Entity) -> bool {
+        self.cursor_confinements.remove(&entity).is_some()
+    }
+
+    /// Lets clicks pass through the given regions of the window associated with our entity, given
+    /// as logical rectangles relative to the top-left of the client area.
+    ///
+    /// `winit` can only toggle the hit test of the whole window, so this is done in software: the
+    /// hit test is disabled every update the cursor is found over one of the regions, and enabled
+    /// again once it isn't. This only has an effect while [`Window::cursor`] has `hit_test`
+    /// enabled, and clicks can only pass through windows that support it, see
+    /// [`Cursor::hit_test`](bevy_window::Cursor::hit_test).
+    ///
+    /// Many platforms stop reporting the cursor to a window that clicks pass through, so the hit
+    /// test is only enabled again once `winit` reports that the cursor left the window.
+    ///
+    /// Returns `false` if the entity has no winit window.
+    pub fn set_hit_test_mask(&mut self, entity: Entity, regions: Vec<Rect>) -> bool {
+        if self.get_window(entity).is_none() {
+            return false;
+        }
+        self.hit_test_masks.insert(entity, regions);
+        true
+    }
+
+    /// Stops letting clicks pass through the regions set with
+    /// [`WinitWindows::set_hit_test_mask`], enabling the hit test again if it was disabled by them.
+    ///
+    /// Returns `false` if the window had no hit test mask.
+    pub fn clear_hit_test_mask(&mut self, entity: Entity) -> bool {
+        if self.hit_test_masked.remove(&entity) {
+            if let Some(winit_window) = self.get_window(entity) {
+                if let Err(err) = winit_window.set_cursor_hittest(true) {
+                    warn!("Could not restore cursor hit test for window {entity:?}: {err:?}");
+                }
+            }
+        }
+        self.hit_test_masks.remove(&entity).is_some()
+    }
+
+    /// Sets or clears the taskbar / title bar icon of the window associated with our entity.
+    ///
+    /// Setting the icon that is already applied does nothing, so this can be called every update.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **iOS / Android / Web / Wayland / macOS:** Unsupported.
+    ///
+    /// Returns `false` if the entity has no winit window, or if the icon is invalid.
+    pub fn set_window_icon(&mut self, entity: Entity, icon: Option<WindowIcon>) -> bool {
+        let Some(winit_window) = self.get_window(entity) else {
+            return false;
+        };
+        if self.window_icons.get(&entity) == icon.as_ref() {
+            return true;
+        }
+        match icon {
+            Some(icon) => match convert_window_icon(&icon) {
+                Ok(winit_icon) => {
+                    winit_window.set_window_icon(Some(winit_icon));
+                    self.window_icons.insert(entity, icon);
+                }
+                Err(err) => {
+                    warn!("Could not set window icon for {entity:?}: {err}");
+                    return false;
+                }
+            },
+            None => {
+                winit_window.set_window_icon(None);
+                self.window_icons.remove(&entity);
+            }
+        }
+        true
+    }
+
+    /// Sets the opacity of the whole window associated with our entity, including its decorations,
+    /// from `0.0` (invisible) to `1.0` (opaque). Values outside that range are clamped.
+    ///
+    /// This is independent of [`Window::transparent`], which only lets the rendered content be
+    /// translucent per pixel.
+    ///
+    /// Returns `false` if the entity has no winit window or the platform doesn't support it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// Only supported on the web, where it sets the opacity of the canvas. Elsewhere a warning is
+    /// logged the first time this is called.
+    pub fn set_window_opacity(&self, entity: Entity, opacity: f32) -> bool {
+        let Some(winit_window) = self.get_window(entity) else {
+            return false;
+        };
+        let opacity = opacity.clamp(0.0, 1.0);
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            use winit::platform::web::WindowExtWebSys;
+            let Some(canvas) = winit_window.canvas() else {
+                return false;
+            };
+            canvas
+                .style()
+                .set_property("opacity", &opacity.to_string())
+                .is_ok()
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            static WARNED: std::sync::Once = std::sync::Once::new();
+            let _ = (winit_window, opacity);
+            WARNED.call_once(|| warn!("Window opacity is not supported on this platform"));
+            false
+        }
+    }
+
+    /// Describes every tracked window entity and the state of its winit window, one per line.
+    ///
+    /// This is meant for diagnostics and bug reports, its format isn't stable.
+    pub fn debug_dump(&self) -> String {
+        use std::fmt::Write;
+
+        let mut entities: Vec<_> = self.entity_to_winit.iter().collect();
+        entities.sort_by_key(|(entity, _)| **entity);
+
+        let mut dump = String::new();
+        for (entity, winit_id) in entities {
+            let _ = write!(dump, "{entity:?}: {winit_id:?}");
+            match self.windows.get(winit_id) {
+                Some(window) => {
+                    let _ = writeln!(
+                        dump,
+                        ", inner size {:?}, outer size {:?}, position {:?}, scale factor {}, \
+                         fullscreen {:?}",
+                        window.inner_size(),
+                        window.outer_size(),
+                        window.outer_position().ok(),
+                        window.scale_factor(),
+                        window.fullscreen(),
+                    );
+                }
+                None => {
+                    let _ = writeln!(dump, ", no winit window");
+                }
+            }
+        }
+        for (winit_id, entity) in &self.winit_to_entity {
+            if self.entity_to_winit.get(entity) != Some(winit_id) {
+                let _ = writeln!(dump, "{winit_id:?} is mapped to {entity:?}, but not back");
+            }
+        }
+        dump
+    }
+
+    /// Remove a window from winit.
+    ///
+    /// This should mostly just be called when the window is closing.
+    pub fn remove_window(&mut self, entity: Entity) -> Option<winit::window::Window> {
+        self.cursor_confinements.remove(&entity);
+        self.hit_test_masks.remove(&entity);
+        self.hit_test_masked.remove(&entity);
+        self.quiet.remove(&entity);
+        self.creation_attempts.remove(&entity);
+        self.aspect_ratios.remove(&entity);
+        self.window_icons.remove(&entity);
+        self.pending_geometry.remove(&entity);
+        self.cursor_visibility.remove(&entity);
+        self.requested_video_modes.remove(&entity);
+        self.windowed_sizes.remove(&entity);
+        let winit_id = self.entity_to_winit.remove(&entity)?;
+        // Don't remove from `winit_to_window_id` so we know the window used to exist.
+        self.windows.remove(&winit_id)
+    }
+}
+
+/// A closure run on a `winit` window by [`WinitWindowCommands`].
+pub type WinitWindowCommand = Box<dyn FnOnce(&winit::window::Window) + Send>;
+
+/// A queue of closures to run on `winit` windows, for code that can't access the
+/// [`WinitWindows`] non-send resource, e.g. background tasks.
+///
+/// [`WinitWindows`] can only be accessed on the main thread, since many `winit` window functions
+/// must be called there. This resource is `Send` and `Sync` and cheap to clone, so a clone can be
+/// moved into a task to queue a closure from any thread. Queued closures are always run on the main
+/// thread, in [`Last`](bevy_app::Last) of the next update, after the changes to [`Window`] of that
+/// update were applied. Closures for entities without a winit window are dropped without being
+/// run.
+#[derive(Resource, Clone, Default)]
+pub struct WinitWindowCommands {
+    queue: Arc<Mutex<Vec<(Entity, WinitWindowCommand)>>>,
+}
+
+impl WinitWindowCommands {
+    /// Queues `command` to run on the winit window associated with our entity.
+    pub fn push(
+        &self,
+        entity: Entity,
+        command: impl FnOnce(&winit::window::Window) + Send + 'static,
+    ) {
+        self.queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((entity, Box::new(command)));
+    }
+
+    /// Takes the queued commands, in the order they were pushed.
+    pub(crate) fn take(&self) -> Vec<(Entity, WinitWindowCommand)> {
+        std::mem::take(&mut *self.queue.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl std::fmt::Debug for WinitWindowCommands {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let len = self
+            .queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len();
+        f.debug_struct("WinitWindowCommands")
+            .field("queued", &len)
+            .finish()
+    }
+}
+
+/// An RGBA window icon, for [`WinitWindows::set_window_icon`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WindowIcon {
+    /// The pixels of the icon, as 8-bit RGBA row by row from the top-left.
+    pub rgba: Vec<u8>,
+    /// The width of the icon, in pixels.
+    pub width: u32,
+    /// The height of the icon, in pixels.
+    pub height: u32,
+}
+
+/// The state of a window captured with [`WinitWindows::snapshot`], e.g. to save and restore the
+/// window layout between sessions.
+///
+/// With the `serde` feature, this can be serialized and deserialized.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WindowSnapshot {
+    /// The physical position of the top-left of the window, including the title bar and borders.
+    ///
+    /// `None` if the platform can't report it (e.g. Wayland).
+    pub position: Option<IVec2>,
+    /// The physical size of the client area of the window.
+    pub size: UVec2,
+    /// The scale factor of the window.
+    pub scale_factor: f64,
+    /// Whether the window was maximized.
+    pub maximized: bool,
+    /// The name of the monitor the window was on, if known.
+    ///
+    /// This is only informative: the window is restored at [`WindowSnapshot::position`].
+    pub monitor_name: Option<String>,
+    /// The fullscreen mode of the window.
+    ///
+    /// `winit` doesn't tell exclusive fullscreen modes apart, so both
+    /// [`WindowMode::Fullscreen`] and [`WindowMode::SizedFullscreen`] are captured as
+    /// [`WindowMode::Fullscreen`].
+    pub mode: WindowMode,
+    /// Whether the window was visible.
+    pub visible: bool,
+}
+
+impl WindowSnapshot {
+    /// Restores the snapshot onto `window`.
+    ///
+    /// Applied to a [`Window`] before it is spawned, the window is created at the captured
+    /// position and size, and maximized, right away, so it doesn't visibly move after appearing.
+    /// Applied to an existing [`Window`], the changes are propagated like any other change.
+    pub fn apply(&self, window: &mut Window) {
+        if let Some(position) = self.position {
+            window.position = WindowPosition::At(position);
+        }
+        // Windows are created with their logical size, which matches the captured physical size
+        // on monitors with the same scale factor.
+        let scale_factor = self.scale_factor as f32;
+        window.resolution.set(
+            self.size.x as f32 / scale_factor,
+            self.size.y as f32 / scale_factor,
+        );
+        window.set_maximized(self.maximized);
+        window.mode = self.mode;
+        window.visible = self.visible;
+    }
+}
+
+/// How long to wait before retrying a window creation that failed `attempts` times, doubling the
+/// `delay` after each further failure.
+pub(crate) fn creation_retry_delay(delay: Duration, attempts: u32) -> Duration {
+    delay.saturating_mul(1 << attempts.saturating_sub(1).min(16))
+}
+
+/// Why the creation of a window entity was deferred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Deferral {
+    /// Its [`WindowHook::ready`] returned `false`.
+    HookNotReady,
+    /// Its [parent](crate::WindowExtras::parent) doesn't have a `winit` window yet.
+    Parent(Entity),
+    /// It has no [`WindowExtras`](crate::WindowExtras), and
+    /// [`WinitSettings::strict_window_components`](crate::WinitSettings::strict_window_components)
+    /// is enabled.
+    MissingExtras,
+}
+
+/// A `winit` [`VideoMode`](winit::monitor::VideoMode), detached from its monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VideoModeInfo {
+    /// The resolution of the video mode, in physical pixels.
+    pub size: UVec2,
+    /// The bit depth of the video mode.
+    pub bit_depth: u16,
+    /// The refresh rate of the video mode, in millihertz.
+    pub refresh_rate_millihertz: u32,
+}
+
+impl From<&winit::monitor::VideoMode> for VideoModeInfo {
+    fn from(videomode: &winit::monitor::VideoMode) -> Self {
+        Self {
+            size: from_physical_size(videomode.size()),
+            bit_depth: videomode.bit_depth(),
+            refresh_rate_millihertz: videomode.refresh_rate_millihertz(),
+        }
+    }
+}
+
+/// Moves a window into a declared [`WindowState`].
+pub(crate) fn apply_window_state(winit_window: &winit::window::Window, state: WindowState) {
+    match state {
+        WindowState::Windowed => {
+            winit_window.set_minimized(false);
+            winit_window.set_maximized(false);
+        }
+        WindowState::Maximized => {
+            winit_window.set_minimized(false);
+            winit_window.set_maximized(true);
+        }
+        WindowState::Minimized => winit_window.set_minimized(true),
+    }
+}
+
+/// Gets the video mode the window is currently using, if it is in exclusive fullscreen.
+pub(crate) fn exclusive_videomode(winit_window: &winit::window::Window) -> Option<VideoModeInfo> {
+    match winit_window.fullscreen() {
+        Some(winit::window::Fullscreen::Exclusive(videomode)) => Some((&videomode).into()),
+        _ => None,
+    }
+}
+
+/// Finds the video mode of a monitor matching `mode` exactly.
+pub(crate) fn find_videomode(
+    monitor: &MonitorHandle,
+    mode: VideoModeInfo,
+) -> Option<winit::monitor::VideoMode> {
+    monitor
+        .video_modes()
+        .find(|videomode| VideoModeInfo::from(videomode) == mode)
+}
+
+/// Gets the "best" video mode which fits the given dimensions.
+///
+/// The heuristic for "best" prioritizes width, height, and refresh rate in that order.
+///
+/// Returns `None` if the monitor doesn't report any video modes.
+pub fn get_fitting_videomode(
+    monitor: &MonitorHandle,
+    width: u32,
+    height: u32,
+) -> Option<winit::monitor::VideoMode> {
+    let mut modes = monitor.video_modes().collect::<Vec<_>>();
+
+    fn abs_diff(a: u32, b: u32) -> u32 {
+        if a > b {
+            return a - b;
+        }
+        b - a
+    }
+
+    modes.sort_by(|a, b| {
+        use std::cmp::Ordering::*;
+        match abs_diff(a.size().width, width).cmp(&abs_diff(b.size().width, width)) {
+            Equal => {
+                match abs_diff(a.size().height, height).cmp(&abs_diff(b.size().height, height)) {
+                    Equal => b
+                        .refresh_rate_millihertz()
+                        .cmp(&a.refresh_rate_millihertz()),
+                    default => default,
+                }
+            }
+            default => default,
+        }
+    });
+
+    modes.into_iter().next()
+}
+
+/// Gets the "best" videomode from a monitor.
+///
+/// The heuristic for "best" prioritizes width, height, and refresh rate in that order.
+///
+/// Returns `None` if the monitor doesn't report any video modes.
+pub fn get_best_videomode(monitor: &MonitorHandle) -> Option<winit::monitor::VideoMode> {
+    let mut modes = monitor.video_modes().collect::<Vec<_>>();
+    modes.sort_by(compare_videomodes);
+
+    modes.into_iter().next()
+}
+
+/// The mode a window requesting the exclusive fullscreen `mode` ends up in, depending on whether
+/// its monitor has a video mode for it.
+///
+/// Monitors without any video modes, e.g. virtual or headless displays, can't go exclusive
+/// fullscreen, so the window falls back to [`WindowMode::BorderlessFullscreen`] on them.
+pub(crate) fn exclusive_fullscreen_fallback(mode: WindowMode, has_videomode: bool) -> WindowMode {
+    match has_videomode {
+        true => mode,
+        false => WindowMode::BorderlessFullscreen,
+    }
+}
+
+/// Lists the video modes of a monitor for display to users, e.g. in a settings menu.
+///
+/// Modes are sorted like [`get_best_videomode`] picks them: by width, height, and refresh rate,
+/// best first. Modes that only differ in bit depth are collapsed into the one with the highest
+/// bit depth.
+pub fn monitor_video_modes_sorted(monitor: &MonitorHandle) -> Vec<VideoModeInfo> {
+    sort_video_modes(
+        monitor
+            .video_modes()
+            .map(|videomode| VideoModeInfo::from(&videomode))
+            .collect(),
+    )
+}
+
+/// Sorts and collapses video modes like [`monitor_video_modes_sorted`].
+fn sort_video_modes(mut modes: Vec<VideoModeInfo>) -> Vec<VideoModeInfo> {
+    modes.sort_by(|a, b| {
+        (b.size.x, b.size.y, b.refresh_rate_millihertz, b.bit_depth).cmp(&(
+            a.size.x,
+            a.size.y,
+            a.refresh_rate_millihertz,
+            a.bit_depth,
+        ))
+    });
+    modes.dedup_by(|a, b| {
+        a.size == b.size && a.refresh_rate_millihertz == b.refresh_rate_millihertz
+    });
+    modes
+}
+
+/// Orders video modes from best to worst, by width, height, and refresh rate in that order.
+fn compare_videomodes(
+    a: &winit::monitor::VideoMode,
+    b: &winit::monitor::VideoMode,
+) -> std::cmp::Ordering {
+    use std::cmp::Ordering::*;
+    match b.size().width.cmp(&a.size().width) {
+        Equal => match b.size().height.cmp(&a.size().height) {
+            Equal => b
+                .refresh_rate_millihertz()
+                .cmp(&a.refresh_rate_millihertz()),
+            default => default,
+        },
+        default => default,
+    }
+}
+
+/// The grab mode to reapply when a window's focus changes, if any.
+///
+/// Many platforms release the grab when the window loses focus, without restoring it on refocus.
+pub(crate) fn regrab_on_focus(focused: bool, grab_mode: CursorGrabMode) -> Option<CursorGrabMode> {
+    (focused && grab_mode != CursorGrabMode::None).then_some(grab_mode)
+}
+
+/// Whether a window is built maximized, given its pending [`Window::set_maximized`] request and
+/// its declared [`WindowExtras::state`].
+///
+/// A declared [`WindowState::Maximized`] wins over a request to unmaximize the window. `None`
+/// leaves it to the platform.
+fn initially_maximized(maximize_request: Option<bool>, state: Option<WindowState>) -> Option<bool> {
+    match state {
+        Some(WindowState::Maximized) => Some(true),
+        _ => maximize_request,
+    }
+}
+
+/// Attempts to set the cursor grab mode, returning the mode that was actually applied.
+///
+/// [`CursorGrabMode::Locked`] isn't supported on every platform, so it falls back to
+/// [`CursorGrabMode::Confined`], and then to [`CursorGrabMode::None`]. The other modes are only
+/// attempted as requested.
+pub(crate) fn attempt_grab(
+    winit_window: &winit::window::Window,
+    grab_mode: CursorGrabMode,
+) -> CursorGrabMode {
+    let modes: &[CursorGrabMode] = match grab_mode {
+        CursorGrabMode::Locked => &[
+            CursorGrabMode::Locked,
+            CursorGrabMode::Confined,
+            CursorGrabMode::None,
+        ],
+        CursorGrabMode::Confined => &[CursorGrabMode::Confined],
+        CursorGrabMode::None => &[CursorGrabMode::None],
+    };
+
+    let mut last_err = None;
+    for &mode in modes {
+        match winit_window.set_cursor_grab(convert_cursor_grab_mode(mode)) {
+            Ok(()) => {
+                if let Some(err) = last_err {
+                    warn!("Unable to set cursor grab mode {grab_mode:?}, fell back to {mode:?}: {err}");
+                }
+                return mode;
+            }
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    if let Some(err) = last_err {
+        let err_desc = match grab_mode {
+            CursorGrabMode::Confined | CursorGrabMode::Locked => "grab",
+            CursorGrabMode::None => "ungrab",
+        };
+
+        bevy_utils::tracing::error!("Unable to {} cursor: {}", err_desc, err);
+    }
+    CursorGrabMode::None
+}
+
+/// Returns the scale factor if it's usable, or `1.0` otherwise.
+///
+/// Some backends (e.g. virtual display drivers) report a scale factor of zero or a non-finite
+/// one, which would otherwise propagate into zero-sized or invalid physical sizes.
+pub(crate) fn sanitize_scale_factor(scale_factor: f64) -> f64 {
+    if scale_factor.is_finite() && scale_factor > 0.0 {
+        scale_factor
+    } else {
+        warn!("winit reported an invalid scale factor of {scale_factor}, using 1.0 instead");
+        1.0
+    }
+}
+
+/// Why a window can't let clicks pass through it, see [`check_hit_test_passthrough`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PassthroughBlocker {
+    Opaque,
+    Decorated,
+    OpaqueAndDecorated,
+}
+
+impl PassthroughBlocker {
+    fn reason(self) -> &'static str {
+        match self {
+            Self::Opaque => "it is opaque",
+            Self::Decorated => "it is decorated",
+            Self::OpaqueAndDecorated => "it is opaque and decorated",
+        }
+    }
+}
+
+/// Warns if cursor passthrough is requested on a window that can't support it.
+///
+/// Most platforms only let clicks pass through windows that are both transparent and
+/// undecorated. Returns what keeps the window from it, which was warned about, if passthrough is
+/// requested but the window doesn't satisfy both requirements, and `None` otherwise, including
+/// when [`Cursor::hit_test`] is enabled.
+///
+/// [`Cursor::hit_test`]: bevy_window::Cursor::hit_test
+pub(crate) fn check_hit_test_passthrough(window: &Window) -> Option<PassthroughBlocker> {
+    let blocker = match (
+        window.cursor.hit_test,
+        window.transparent,
+        window.decorations,
+    ) {
+        (true, _, _) | (false, true, false) => return None,
+        (false, false, false) => PassthroughBlocker::Opaque,
+        (false, true, true) => PassthroughBlocker::Decorated,
+        (false, false, true) => PassthroughBlocker::OpaqueAndDecorated,
+    };
+    warn!(
+        "`cursor.hit_test` is disabled for window {:?}, but {}. Clicks may not pass through \
+        it on all platforms; create the window with `transparent: true` and set \
+        `decorations: false`.",
+        window.title,
+        blocker.reason()
+    );
+    Some(blocker)
+}
+
+/// Compute the physical window position for a given [`WindowPosition`].
+// Ideally we could generify this across window backends, but we only really have winit atm
+// so whatever.
+pub fn winit_window_position(
+    position: &WindowPosition,
+    resolution: &WindowResolution,
+    mut available_monitors: impl Iterator<Item = MonitorHandle>,
+    primary_monitor: Option<MonitorHandle>,
+    current_monitor: Option<MonitorHandle>,
+) -> Option<PhysicalPosition<i32>> {
+    match position {
+        WindowPosition::Automatic => {
+            /* Window manager will handle position */
+            None
+        }
+        WindowPosition::Centered(monitor_selection) => {
+            use bevy_window::MonitorSelection::*;
+            let maybe_monitor = match monitor_selection {
+                Current => {
+                    if current_monitor.is_none() {
+                        warn!("Can't select current monitor on window creation or cannot find current monitor!");
+                    }
+                    current_monitor
+                }
+                Primary => primary_monitor,
+                Index(n) => available_monitors.nth(*n),
+            };
+
+            if let Some(monitor) = maybe_monitor {
+                let screen_size = monitor.size();
+
+                // We use the monitors scale factor here since `WindowResolution.scale_factor` is
+                // not yet populated when windows are created during plugin setup.
+                let scale_factor = monitor.scale_factor();
+
+                // Logical to physical window size
+                let (width, height): (u32, u32) =
+                    LogicalSize::new(resolution.width(), resolution.height())
+                        .to_physical::<u32>(scale_factor)
+                        .into();
+
+                let position = PhysicalPosition {
+                    x: screen_size.width.saturating_sub(width) as f64 / 2.
+                        + monitor.position().x as f64,
+                    y: screen_size.height.saturating_sub(height) as f64 / 2.
+                        + monitor.position().y as f64,
+                };
+
+                Some(position.cast::<i32>())
+            } else {
+                warn!("Couldn't get monitor selected with: {monitor_selection:?}");
+                None
+            }
+        }
+        WindowPosition::At(position) => {
+            Some(PhysicalPosition::new(position[0] as f64, position[1] as f64).cast::<i32>())
+        }
+    }
+}
+
+/// Fixes resize `constraints` that can't be applied as they are.
+///
+/// Like [`WindowResizeConstraints::check_constraints`], maximums below their minimum are raised to
+/// it; on top of that, non-finite minimums are reset to the default, and `NaN` maximums are
+/// treated as unbounded. This means `min <= max` holds for the returned constraints, which come
+/// with whether anything had to be fixed.
+///
+/// This runs on every resize, so it doesn't warn: use [`warn_invalid_constraints`] once when the
+/// constraints change.
+pub(crate) fn validate_constraints(
+    constraints: &WindowResizeConstraints,
+) -> (WindowResizeConstraints, bool) {
+    let default = WindowResizeConstraints::default();
+    let original = constraints;
+    let mut constraints = *constraints;
+    if !constraints.min_width.is_finite() {
+        constraints.min_width = default.min_width;
+    }
+    if !constraints.min_height.is_finite() {
+        constraints.min_height = default.min_height;
+    }
+    if constraints.max_width.is_nan() {
+        constraints.max_width = f32::INFINITY;
+    }
+    if constraints.max_height.is_nan() {
+        constraints.max_height = f32::INFINITY;
+    }
+    constraints.min_width = constraints.min_width.max(1.);
+    constraints.min_height = constraints.min_height.max(1.);
+    constraints.max_width = constraints.max_width.max(constraints.min_width);
+    constraints.max_height = constraints.max_height.max(constraints.min_height);
+    // `NaN`s never compare equal, so they count as fixed as well.
+    (constraints, constraints != *original)
+}
+
+/// Warns about the resize `constraints` that [`validate_constraints`] has to fix.
+pub(crate) fn warn_invalid_constraints(constraints: &WindowResizeConstraints) {
+    let (validated, fixed) = validate_constraints(constraints);
+    if !fixed {
+        return;
+    }
+    if !constraints.min_width.is_finite() {
+        warn!(
+            "Ignoring the minimum width {} of the resize constraints",
+            constraints.min_width
+        );
+    }
+    if !constraints.min_height.is_finite() {
+        warn!(
+            "Ignoring the minimum height {} of the resize constraints",
+            constraints.min_height
+        );
+    }
+    if constraints.max_width.is_nan() {
+        warn!("Ignoring the maximum width NaN of the resize constraints");
+    }
+    if constraints.max_height.is_nan() {
+        warn!("Ignoring the maximum height NaN of the resize constraints");
+    }
+    if constraints.max_width < validated.min_width {
+        warn!(
+            "The given maximum width {} is smaller than the minimum width {}",
+            constraints.max_width, validated.min_width
+        );
+    }
+    if constraints.max_height < validated.min_height {
+        warn!(
+            "The given maximum height {} is smaller than the minimum height {}",
+            constraints.max_height, validated.min_height
+        );
+    }
+}
+
+/// The minimum and maximum inner size for resize `constraints`, in physical pixels if `physical`
+/// and in logical pixels otherwise.
+///
+/// The maximum is `None` if it is unbounded in either direction.
+pub(crate) fn inner_size_limits(
+    constraints: &WindowResizeConstraints,
+    physical: bool,
+) -> (Size, Option<Size>) {
+    let (constraints, _) = validate_constraints(constraints);
+    let size = |width: f32, height: f32| -> Size {
+        if physical {
+            PhysicalSize::new(width.round() as u32, height.round() as u32).into()
+        } else {
+            LogicalSize::new(width, height).into()
+        }
+    };
+    let max_inner_size = (constraints.max_width.is_finite() && constraints.max_height.is_finite())
+        .then(|| size(constraints.max_width, constraints.max_height));
+    (
+        size(constraints.min_width, constraints.min_height),
+        max_inner_size,
+    )
+}
+
+/// Applies resize `constraints` to a `winit` window, in physical pixels if `physical` and in
+/// logical pixels otherwise.
+pub(crate) fn apply_resize_constraints(
+    winit_window: &winit::window::Window,
+    constraints: &WindowResizeConstraints,
+    physical: bool,
+) {
+    let (min_inner_size, max_inner_size) = inner_size_limits(constraints, physical);
+    winit_window.set_min_inner_size(Some(min_inner_size));
+    // Also clears a maximum left over from `pin_inner_size`.
+    winit_window.set_max_inner_size(max_inner_size);
+}
+
+/// Pins the inner size of a non-resizable `winit` window to the physical `size`, by setting both
+/// its minimum and maximum inner size to it.
+pub(crate) fn pin_inner_size(winit_window: &winit::window::Window, size: PhysicalSize<u32>) {
+    winit_window.set_min_inner_size(Some(size));
+    winit_window.set_max_inner_size(Some(size));
+}
+
+/// The physical inner size a non-resizable window of logical `size` is pinned to at
+/// `scale_factor`, within its resize `constraints`, in physical pixels if `physical`.
+pub(crate) fn pinned_inner_size(
+    size: LogicalSize<f32>,
+    constraints: &WindowResizeConstraints,
+    scale_factor: f64,
+    physical: bool,
+) -> PhysicalSize<u32> {
+    let constraints_scale_factor = match physical {
+        true => 1.0,
+        false => scale_factor,
+    };
+    clamp_to_constraints(
+        size.to_physical(scale_factor),
+        constraints,
+        constraints_scale_factor,
+    )
+}
+
+/// Adjusts a physical inner window `size` to an aspect `ratio` (width / height).
+///
+/// The dimension that changed the most relative to the `previous` size is kept, and the other one
+/// is computed from it. If neither changed, the width is kept.
+pub(crate) fn fit_aspect_ratio(
+    size: PhysicalSize<u32>,
+    previous: PhysicalSize<u32>,
+    ratio: f32,
+) -> PhysicalSize<u32> {
+    let change = |value: u32, previous: u32| (value as f32 / previous.max(1) as f32 - 1.).abs();
+    let ratio = ratio as f64;
+    if change(size.height, previous.height) > change(size.width, previous.width) {
+        PhysicalSize::new((size.height as f64 * ratio).round() as u32, size.height)
+    } else {
+        PhysicalSize::new(size.width, (size.width as f64 / ratio).round() as u32)
+    }
+}
+
+/// Clamps a physical inner window `size` to the logical resize `constraints` at `scale_factor`.
+///
+/// Pass a `scale_factor` of `1.0` for constraints in physical pixels.
+pub(crate) fn clamp_to_constraints(
+    size: PhysicalSize<u32>,
+    constraints: &WindowResizeConstraints,
+    scale_factor: f64,
+) -> PhysicalSize<u32> {
+    let (constraints, _) = validate_constraints(constraints);
+    // Float to int casts saturate, so unbounded maximums become `u32::MAX`.
+    let clamp = |value: u32, min: f32, max: f32| {
+        let min = (min as f64 * scale_factor).ceil() as u32;
+        let max = (max as f64 * scale_factor).floor() as u32;
+        value.min(max).max(min)
+    };
+    PhysicalSize::new(
+        clamp(size.width, constraints.min_width, constraints.max_width),
+        clamp(size.height, constraints.min_height, constraints.max_height),
+    )
+}
+
+/// The position a window is moved to when its [`WindowPosition`] can't be resolved, according to
+/// the `fallback`.
+pub(crate) fn fallback_window_position(
+    fallback: PositionFallback,
+    resolution: &WindowResolution,
+    primary_monitor: Option<MonitorHandle>,
+) -> Option<PhysicalPosition<i32>> {
+    let position = match fallback {
+        PositionFallback::LeaveAsIs => None,
+        PositionFallback::CenterOnPrimary => winit_window_position(
+            &WindowPosition::Centered(MonitorSelection::Primary),
+            resolution,
+            std::iter::empty(),
+            primary_monitor,
+            None,
+        ),
+        PositionFallback::TopLeft => {
+            Some(primary_monitor.map_or(PhysicalPosition::new(0, 0), |monitor| monitor.position()))
+        }
+    };
+    debug!("Window position can't be resolved, using fallback {fallback:?}: {position:?}");
+    position
+}
+
+/// Clamps an outer window `position` so that at least `min_visible` physical pixels of the window
+/// remain on one of the `available_monitors`, in both directions.
+///
+/// Returns `None` if the position is already visible enough, or if there are no monitors to clamp
+/// to. See [`clamp_rect_to_monitors`].
+pub(crate) fn clamp_to_monitors(
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    available_monitors: impl Iterator<Item = MonitorHandle>,
+    min_visible: u32,
+) -> Option<PhysicalPosition<i32>> {
+    let position = from_physical_position(position);
+    let window = IRect::from_corners(position, position + from_physical_size(size).as_ivec2());
+    let monitors: Vec<IRect> = available_monitors
+        .map(|monitor| {
+            let min = from_physical_position(monitor.position());
+            IRect::from_corners(min, min + from_physical_size(monitor.size()).as_ivec2())
+        })
+        .collect();
+    clamp_rect_to_monitors(window, &monitors, min_visible).map(to_physical_position)
+}
+
+/// Clamps the physical outer rectangle of a `window` so that at least `min_visible` pixels of it
+/// remain on one of the `monitors`, in both directions, returning its new top-left corner.
+///
+/// Returns `None` if the window is already visible enough, or if there are no monitors to clamp
+/// to. The window is moved onto the monitor closest to it, keeping its top edge inside so that the
+/// title bar stays reachable.
+fn clamp_rect_to_monitors(window: IRect, monitors: &[IRect], min_visible: u32) -> Option<IVec2> {
+    let size = window.size();
+    let min_visible = min_visible as i32;
+    let visible_enough = |monitor: &IRect| {
+        let overlap = window.intersect(*monitor);
+        !overlap.is_empty()
+            && overlap.width() >= min_visible.min(window.width())
+            && overlap.height() >= min_visible.min(window.height())
+    };
+    if monitors.is_empty() || monitors.iter().any(visible_enough) {
+        return None;
+    }
+
+    let distance = |monitor: &IRect| {
+        let closest = window.center().clamp(monitor.min, monitor.max);
+        (window.center() - closest).length_squared()
+    };
+    let monitor = monitors.iter().min_by_key(|monitor| distance(monitor))?;
+
+    let visible = size.min(IVec2::splat(min_visible));
+    let min = IVec2::new(monitor.min.x - size.x + visible.x, monitor.min.y);
+    let max = (monitor.max - visible).max(min);
+    Some(window.min.clamp(min, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(
+        width: u32,
+        height: u32,
+        refresh_rate_millihertz: u32,
+        bit_depth: u16,
+    ) -> VideoModeInfo {
+        VideoModeInfo {
+            size: UVec2::new(width, height),
+            bit_depth,
+            refresh_rate_millihertz,
+        }
+    }
+
+    #[test]
+    fn video_modes_are_sorted_best_first_without_duplicates() {
+        let modes = vec![
+            mode(1280, 720, 60_000, 32),
+            mode(1920, 1080, 60_000, 24),
+            mode(1920, 1080, 144_000, 32),
+            mode(1920, 1080, 60_000, 32),
+            mode(1280, 720, 60_000, 32),
+            mode(1920, 1200, 60_000, 32),
+            mode(1920, 1080, 60_000, 16),
+        ];
+        assert_eq!(
+            sort_video_modes(modes),
+            vec![
+                mode(1920, 1200, 60_000, 32),
+                mode(1920, 1080, 144_000, 32),
+                mode(1920, 1080, 60_000, 32),
+                mode(1280, 720, 60_000, 32),
+            ]
+        );
+        assert!(sort_video_modes(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn aspect_ratio_follows_the_dimension_that_changed() {
+        let previous = PhysicalSize::new(1600, 900);
+        let ratio = 16.0 / 9.0;
+        assert_eq!(
+            fit_aspect_ratio(PhysicalSize::new(1920, 900), previous, ratio),
+            PhysicalSize::new(1920, 1080)
+        );
+        assert_eq!(
+            fit_aspect_ratio(PhysicalSize::new(1600, 450), previous, ratio),
+            PhysicalSize::new(800, 450)
+        );
+        // Without a change, the width is kept.
+        assert_eq!(
+            fit_aspect_ratio(previous, previous, 2.0),
+            PhysicalSize::new(1600, 800)
+        );
+    }
+
+    #[test]
+    fn invalid_constraints_are_fixed() {
+        let (constraints, fixed) = validate_constraints(&WindowResizeConstraints {
+            min_width: 800.,
+            min_height: f32::NAN,
+            max_width: 400.,
+            max_height: f32::NAN,
+        });
+        let default = WindowResizeConstraints::default();
+        assert_eq!(constraints.min_width, 800.);
+        assert_eq!(constraints.max_width, 800.);
+        assert_eq!(constraints.min_height, default.min_height);
+        assert_eq!(constraints.max_height, f32::INFINITY);
+        assert!(fixed);
+
+        let (constraints, fixed) = validate_constraints(&WindowResizeConstraints {
+            min_width: f32::NEG_INFINITY,
+            min_height: -5.,
+            max_width: f32::INFINITY,
+            max_height: 0.,
+        });
+        assert_eq!(constraints.min_width, default.min_width);
+        assert_eq!(constraints.min_height, 1.);
+        assert_eq!(constraints.max_width, f32::INFINITY);
+        assert_eq!(constraints.max_height, 1.);
+        assert!(fixed);
+
+        // Valid constraints are left alone.
+        let valid = WindowResizeConstraints {
+            max_width: 1920.,
+            ..default
+        };
+        assert_eq!(validate_constraints(&valid), (valid, false));
+        assert_eq!(validate_constraints(&default), (default, false));
+    }
+
+    #[test]
+    fn physical_constraints_are_exact_at_any_scale_factor() {
+        let constraints = WindowResizeConstraints {
+            min_width: 321.,
+            min_height: 241.,
+            max_width: 641.,
+            max_height: 481.,
+        };
+        let scale_factor = 1.5;
+
+        let (min, max) = inner_size_limits(&constraints, true);
+        assert_eq!(
+            min.to_physical::<u32>(scale_factor),
+            PhysicalSize::new(321, 241)
+        );
+        assert_eq!(
+            max.map(|max| max.to_physical::<u32>(scale_factor)),
+            Some(PhysicalSize::new(641, 481))
+        );
+
+        // Logical constraints are scaled instead.
+        let (min, _) = inner_size_limits(&constraints, false);
+        assert_eq!(
+            min.to_physical::<u32>(scale_factor),
+            PhysicalSize::new(482, 362)
+        );
+    }
+
+    #[test]
+    fn sizes_are_clamped_to_conflicting_constraints() {
+        // The maximum is below the minimum, so the minimum wins.
+        let constraints = WindowResizeConstraints {
+            min_width: 400.,
+            min_height: 300.,
+            max_width: 200.,
+            max_height: 100.,
+        };
+        assert_eq!(
+            clamp_to_constraints(PhysicalSize::new(1000, 50), &constraints, 1.0),
+            PhysicalSize::new(400, 300)
+        );
+        assert_eq!(
+            clamp_to_constraints(PhysicalSize::new(1000, 50), &constraints, 2.0),
+            PhysicalSize::new(800, 600)
+        );
+
+        // Fractional limits are rounded inwards, so the clamped size respects both.
+        let constraints = WindowResizeConstraints {
+            min_width: 100.5,
+            min_height: 100.,
+            max_width: 200.5,
+            max_height: 100.,
+        };
+        assert_eq!(
+            clamp_to_constraints(PhysicalSize::new(10, 10), &constraints, 1.0),
+            PhysicalSize::new(101, 100)
+        );
+        assert_eq!(
+            clamp_to_constraints(PhysicalSize::new(1000, 1000), &constraints, 1.0),
+            PhysicalSize::new(200, 100)
+        );
+    }
+
+    #[test]
+    fn invalid_scale_factors_fall_back_to_one() {
+        for scale_factor in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 0.0, -0.0, -2.0] {
+            assert_eq!(sanitize_scale_factor(scale_factor), 1.0, "{scale_factor}");
+        }
+        // Large but finite factors are usable, if unusual.
+        for scale_factor in [0.5, 1.25, 4.0, 1e6, f64::MAX] {
+            assert_eq!(sanitize_scale_factor(scale_factor), scale_factor);
+        }
+    }
+
+    #[test]
+    fn off_screen_windows_are_clamped_onto_the_closest_monitor() {
+        let monitors = [
+            IRect::new(0, 0, 1920, 1080),
+            IRect::new(1920, 0, 1920 + 1280, 1024),
+        ];
+        let window = |x: i32, y: i32| IRect::new(x, y, x + 800, y + 600);
+
+        // Visible enough, on either monitor or across both.
+        assert_eq!(
+            clamp_rect_to_monitors(window(100, 100), &monitors, 32),
+            None
+        );
+        assert_eq!(
+            clamp_rect_to_monitors(window(1500, 100), &monitors, 32),
+            None
+        );
+        assert_eq!(
+            clamp_rect_to_monitors(window(-768, 100), &monitors, 32),
+            None
+        );
+
+        // Too far left, above, and beyond the right monitor.
+        assert_eq!(
+            clamp_rect_to_monitors(window(-790, 100), &monitors, 32),
+            Some(IVec2::new(-768, 100))
+        );
+        assert_eq!(
+            clamp_rect_to_monitors(window(100, -590), &monitors, 32),
+            Some(IVec2::new(100, 0))
+        );
+        assert_eq!(
+            clamp_rect_to_monitors(window(5000, 2000), &monitors, 32),
+            Some(IVec2::new(3200 - 32, 1024 - 32))
+        );
+
+        // Windows smaller than `min_visible` have to be fully visible.
+        let small = IRect::new(-10, 10, 10, 30);
+        assert_eq!(
+            clamp_rect_to_monitors(small, &monitors, 32),
+            Some(IVec2::new(0, 10))
+        );
+
+        assert_eq!(clamp_rect_to_monitors(window(-5000, 0), &[], 32), None);
+    }
+
+    #[test]
+    fn pinned_sizes_follow_the_scale_factor() {
+        let size = LogicalSize::new(800., 600.);
+        let constraints = WindowResizeConstraints {
+            min_width: 640.,
+            min_height: 480.,
+            max_width: 640.,
+            max_height: 480.,
+        };
+        // Constraints with min == max win over the requested size, at any scale factor.
+        assert_eq!(
+            pinned_inner_size(size, &constraints, 1.5, false),
+            PhysicalSize::new(960, 720)
+        );
+        assert_eq!(
+            pinned_inner_size(size, &constraints, 1.5, true),
+            PhysicalSize::new(640, 480)
+        );
+        assert_eq!(
+            pinned_inner_size(size, &WindowResizeConstraints::default(), 2.0, false),
+            PhysicalSize::new(1600, 1200)
+        );
+    }
+
+    #[test]
+    fn hit_test_passthrough_needs_transparent_undecorated_windows() {
+        let window = |hit_test: bool, transparent: bool, decorations: bool| Window {
+            cursor: bevy_window::Cursor {
+                hit_test,
+                ..Default::default()
+            },
+            transparent,
+            decorations,
+            ..Default::default()
+        };
+        assert_eq!(check_hit_test_passthrough(&window(true, false, true)), None);
+        assert_eq!(
+            check_hit_test_passthrough(&window(false, true, false)),
+            None
+        );
+        assert_eq!(
+            check_hit_test_passthrough(&window(false, false, false)),
+            Some(PassthroughBlocker::Opaque)
+        );
+        assert_eq!(
+            check_hit_test_passthrough(&window(false, true, true)),
+            Some(PassthroughBlocker::Decorated)
+        );
+        assert_eq!(
+            check_hit_test_passthrough(&window(false, false, true)),
+            Some(PassthroughBlocker::OpaqueAndDecorated)
+        );
+    }
+
+    #[test]
+    fn creation_retries_back_off() {
+        let delay = Duration::from_millis(100);
+        assert_eq!(creation_retry_delay(delay, 1), delay);
+        assert_eq!(creation_retry_delay(delay, 3), delay * 4);
+        assert_eq!(creation_retry_delay(delay, 100), delay * (1 << 16));
+
+        let mut winit_windows = WinitWindows::default();
+        assert_eq!(winit_windows.next_creation_retry(3, delay), None);
+        let now = Instant::now();
+        winit_windows
+            .creation_attempts
+            .insert(Entity::from_raw(1), (2, now));
+        winit_windows
+            .creation_attempts
+            .insert(Entity::from_raw(2), (1, now));
+        // Exhausted retries aren't waited for.
+        winit_windows
+            .creation_attempts
+            .insert(Entity::from_raw(3), (4, now));
+        assert_eq!(
+            winit_windows.next_creation_retry(3, delay),
+            Some(now + delay)
+        );
+    }
+
+    #[test]
+    fn grabs_are_reapplied_on_refocus() {
+        let grab_mode = CursorGrabMode::Locked;
+        assert_eq!(regrab_on_focus(false, grab_mode), None);
+        assert_eq!(regrab_on_focus(true, grab_mode), Some(grab_mode));
+        assert_eq!(regrab_on_focus(false, CursorGrabMode::None), None);
+        assert_eq!(regrab_on_focus(true, CursorGrabMode::None), None);
+    }
+
+    #[test]
+    fn position_fallbacks_without_monitors() {
+        let resolution = WindowResolution::default();
+        let fallback = |fallback| fallback_window_position(fallback, &resolution, None);
+        assert_eq!(fallback(PositionFallback::LeaveAsIs), None);
+        assert_eq!(fallback(PositionFallback::CenterOnPrimary), None);
+        assert_eq!(
+            fallback(PositionFallback::TopLeft),
+            Some(PhysicalPosition::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn monitors_without_video_modes_fall_back_to_borderless() {
+        for mode in [WindowMode::Fullscreen, WindowMode::SizedFullscreen] {
+            assert_eq!(exclusive_fullscreen_fallback(mode, true), mode);
+            assert_eq!(
+                exclusive_fullscreen_fallback(mode, false),
+                WindowMode::BorderlessFullscreen
+            );
+        }
+    }
+
+    #[test]
+    fn windows_can_be_built_maximized() {
+        let maximized = Some(WindowState::Maximized);
+        assert_eq!(initially_maximized(None, None), None);
+        assert_eq!(initially_maximized(Some(true), None), Some(true));
+        assert_eq!(initially_maximized(Some(false), None), Some(false));
+        assert_eq!(initially_maximized(None, maximized), Some(true));
+        assert_eq!(initially_maximized(Some(false), maximized), Some(true));
+        assert_eq!(initially_maximized(None, Some(WindowState::Windowed)), None);
+
+        // The request is consumed by building the window, so `changed_windows` doesn't repeat it.
+        let mut window = Window::default();
+        window.set_maximized(true);
+        assert_eq!(window.internal.take_maximize_request(), Some(true));
+        assert_eq!(window.internal.take_maximize_request(), None);
+    }
 }